@@ -0,0 +1,35 @@
+use crate::{protected_command, VaultState};
+use password_manager_backend::{
+    audit::{self, breach::BreachList, strength::{self, PasswordStrength}, AuditOptions, AuditReport},
+    error::app_error::AppError,
+};
+use std::path::PathBuf;
+use tauri::State;
+
+// Live feedback while a password is typed. Available before a vault exists so the setup screen can use it.
+#[tauri::command]
+pub async fn estimate_password_strength(password: String) -> Result<PasswordStrength, AppError> {
+    Ok(strength::estimate(&password))
+}
+
+// Scores every stored password and reports weak, reused and old ones, plus compromised ones when given
+// a downloaded breached password list. The report is computed on demand and never written to disk.
+#[tauri::command]
+pub async fn run_security_audit(
+    state: State<'_, VaultState>,
+    options: Option<AuditOptions>,
+    breach_list_path: Option<String>,
+) -> Result<AuditReport, AppError> {
+    protected_command!(state, {
+        let breach_list = breach_list_path
+            .map(|path| BreachList::open(&PathBuf::from(path)))
+            .transpose()?;
+
+        let vault_manager_lock = state.vault_manager.read().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        audit::run_audit(&manager.entry_collection, key_hierarchy, &options.unwrap_or_default(), breach_list.as_ref())
+    })
+}
@@ -1,5 +1,15 @@
-use password_manager_backend::{crypto::{KeyDerivation, SecureMemory}, error::app_error::AppError};
-use crate::{protected_command, helpers, VaultState};
+use chrono::Utc;
+use password_manager_backend::{
+    _vault::auto_lock::AutoLockSettings,
+    audit::strength::PasswordPolicy,
+    auth::{
+        quick_unlock::{QuickUnlock, QuickUnlockStatus},
+        unlock_throttle::{UnlockPolicy, UnlockThrottle, UnlockThrottleStatus},
+    },
+    crypto::{KeyDerivation, SecureMemory},
+    error::{app_error::AppError, auth_error::AuthError, vault_error::VaultError},
+};
+use crate::{auto_backup, protected_command, helpers, VaultState};
 
 use tauri::State;
 
@@ -16,28 +26,140 @@ pub async fn login(
     let mut vault_manager_lock = state.vault_manager.write().await;
     let mut key_hierarchy_lock = state.key_hierarchy.write().await;
 
+    // Refuse early while the backoff from earlier failures is still running
+    let mut throttle = state.storage.load_unlock_throttle().await?;
+    throttle.check(Utc::now())?;
+
     println!("Locks acquired, loading vault");
     // Load the vault from storage
     let mut loaded_manager = state.storage.load_vault().await?;
 
     println!("Vault loaded, attempting to login");
     // Validate the master password and login
-    let key_hierarchy = loaded_manager.login(SecureMemory::new(password))?;
+    let key_hierarchy = match loaded_manager.login(SecureMemory::new(password)) {
+        Ok(key_hierarchy) => key_hierarchy,
+        Err(VaultError::AuthError(AuthError::InvalidPassword)) => {
+            return Err(record_failed_unlock(&state, &mut throttle).await?);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    record_successful_unlock(&state, &mut throttle, &loaded_manager.unlock_policy).await?;
 
     println!("Login successful, saving vault");
+    // Re-saving writes the vault in the current format, sealing any entries that were migrated from older files
+    state.storage.save_vault(&mut loaded_manager, &key_hierarchy).await?;
+
+    // Clear out files left behind by an interrupted delete or a restore
+    state.storage.remove_orphaned_attachments(&loaded_manager.entry_collection.attachment_ids()).await?;
 
     // Update state while still holding the locks
+    let auto_lock_settings = loaded_manager.auto_lock_settings;
     vault_manager_lock.replace(loaded_manager);
     key_hierarchy_lock.replace(key_hierarchy);
 
     // Set session active after successful login
-    state.set_session_active(true).await;
+    state.start_session(auto_lock_settings).await;
 
     println!("Successfully logged in");
     
     Ok(())
 }
 
+// Counts a wrong master password and wipes the vault once the unlock policy's limit is reached.
+// Returns the error to report for the attempt.
+async fn record_failed_unlock(
+    state: &VaultState,
+    throttle: &mut UnlockThrottle,
+) -> Result<AppError, AppError> {
+    throttle.record_failure(Utc::now());
+
+    if throttle.should_wipe() {
+        println!("Failed attempt limit reached, wiping vault");
+        state.storage.wipe_vault().await?;
+        state.clear_quick_unlock().await;
+        return Ok(AuthError::VaultWiped.into());
+    }
+
+    state.storage.save_unlock_throttle(throttle).await?;
+    Ok(AuthError::InvalidPassword.into())
+}
+
+// Clears the failure count and refreshes the throttle's copy of the unlock policy from a vault
+// that just passed its integrity check
+async fn record_successful_unlock(
+    state: &VaultState,
+    throttle: &mut UnlockThrottle,
+    policy: &UnlockPolicy,
+) -> Result<(), AppError> {
+    let policy_changed = throttle.set_policy(policy);
+    if throttle.failed_attempts() > 0 || policy_changed {
+        throttle.reset();
+        state.storage.save_unlock_throttle(throttle).await?;
+    }
+    Ok(())
+}
+
+/// Copies a changed unlock policy to the throttle, which enforces it while the vault is locked
+pub(crate) async fn sync_unlock_policy(state: &VaultState, policy: &UnlockPolicy) -> Result<(), AppError> {
+    let mut throttle = state.storage.load_unlock_throttle().await?;
+    if throttle.set_policy(policy) {
+        state.storage.save_unlock_throttle(&throttle).await?;
+    }
+    Ok(())
+}
+
+/// Recovery path for a login that failed with `VaultTampered`: swaps in the newest backup that
+/// verifies with this password and logs into it
+#[tauri::command]
+pub async fn restore_verified_backup(
+    state: State<'_, VaultState>,
+    password: String
+) -> Result<(), AppError> {
+    let mut vault_manager_lock = state.vault_manager.write().await;
+    let mut key_hierarchy_lock = state.key_hierarchy.write().await;
+
+    // Trying a password against the vault and its backups is still an unlock attempt, counted once per call
+    let mut throttle = state.storage.load_unlock_throttle().await?;
+    throttle.check(Utc::now())?;
+    let password = SecureMemory::new(password);
+
+    // Only a vault that fails its integrity check is replaced; a healthy one would lose every change since the backup
+    let mut current_manager = state.storage.load_vault().await?;
+    match current_manager.login(SecureMemory::new(password.as_ref().clone())) {
+        Err(VaultError::VaultTampered) => {}
+        Err(VaultError::AuthError(AuthError::InvalidPassword)) => {
+            return Err(record_failed_unlock(&state, &mut throttle).await?);
+        }
+        Err(e) => return Err(e.into()),
+        Ok(_) => {
+            return Err(VaultError::ValidationError(
+                "The vault passed its integrity check, so no backup was restored".to_string()
+            ).into());
+        }
+    }
+
+    let (mut restored_manager, key_hierarchy) = match state.storage.restore_latest_verified_backup(&password).await {
+        Err(AppError::AuthError(AuthError::InvalidPassword)) => {
+            return Err(record_failed_unlock(&state, &mut throttle).await?);
+        }
+        result => result?,
+    };
+    record_successful_unlock(&state, &mut throttle, &restored_manager.unlock_policy).await?;
+    // The backup may use an older master password than the one the PIN wraps
+    state.clear_quick_unlock().await;
+
+    println!("Verified backup restored, saving vault");
+    state.storage.save_vault(&mut restored_manager, &key_hierarchy).await?;
+
+    let auto_lock_settings = restored_manager.auto_lock_settings;
+    vault_manager_lock.replace(restored_manager);
+    key_hierarchy_lock.replace(key_hierarchy);
+    state.start_session(auto_lock_settings).await;
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn logout(
     state: State<'_, VaultState>
@@ -61,7 +183,8 @@ pub async fn logout(
 
     // Save vault state before clearing
     println!("Saving vault state before logout");
-    state.storage.save_vault(manager).await?;
+    let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+    state.storage.save_vault(manager, key_hierarchy).await?;
 
     println!("Auth Service Key Derivation:\n{:?}", manager.auth_service.key_derivation());
     println!("Vault Manager Key Derivation:\n{:?}", manager.key_derivation_settings);
@@ -73,8 +196,9 @@ pub async fn logout(
     }
 
     println!("Deactivating session");
-    // Deactivate session before clearing state
+    // Deactivate session before clearing state; an explicit logout also forgets the PIN
     state.set_session_active(false).await;
+    state.clear_quick_unlock().await;
 
     // Clear the vault manager state
     println!("Clearing vault state");
@@ -88,19 +212,30 @@ pub async fn change_master_password(
     state: State<'_, VaultState>, 
     old_password: String, 
     new_password: String, 
-    confirm_password: String
+    confirm_password: String,
+    breach_list_path: Option<String>,
 ) -> Result<(), AppError> {
     protected_command!(state, {
-        helpers::validate_password(&new_password, &confirm_password)?;
-
         let mut vault_manager_lock = state.vault_manager.write().await;
         let mut key_hierarchy_lock = state.key_hierarchy.write().await;
 
         let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        helpers::validate_password(&new_password, &confirm_password, &manager.password_policy, breach_list_path.as_deref())?;
+
+        // Checked before the snapshot so a wrong password can't be used to force backup writes and pruning
+        if !manager.auth_service.verify_master_password(SecureMemory::new(old_password.clone()))? {
+            return Err(AuthError::InvalidPassword.into());
+        }
+        let current_key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        auto_backup::snapshot_before_change(&state.storage, manager, current_key_hierarchy).await?;
+
         let key_hierarchy = manager.change_master_password(SecureMemory::new(old_password), SecureMemory::new(new_password))?;
 
-        state.storage.save_vault(manager).await?;
+        state.storage.save_vault(manager, &key_hierarchy).await?;
         key_hierarchy_lock.replace(key_hierarchy);
+
+        // The PIN wraps the old master key
+        state.clear_quick_unlock().await;
         
         Ok(())
     })
@@ -119,6 +254,12 @@ pub async fn update_key_derivation(
         let mut key_hierarchy_lock = state.key_hierarchy.write().await;
         
         let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        if !manager.auth_service.verify_master_password(SecureMemory::new(master_password.clone()))? {
+            return Err(AuthError::InvalidPassword.into());
+        }
+        let current_key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        auto_backup::snapshot_before_change(&state.storage, manager, current_key_hierarchy).await?;
+
         let new_key_hierarchy = manager.update_key_derivation(
             SecureMemory::new(master_password), 
             settings
@@ -131,6 +272,7 @@ pub async fn update_key_derivation(
         println!("New Key Hierarchy: {:?}\n\n", new_key_hierarchy);
 
         key_hierarchy_lock.replace(new_key_hierarchy);
+        state.clear_quick_unlock().await;
 
         println!("NOW UPDATED Key Hierarchy: {:?}\n\n\n\n", key_hierarchy_lock.as_ref());
 
@@ -140,7 +282,8 @@ pub async fn update_key_derivation(
         println!("\n\nFINAL MEK {:?}\n\n\n\n", key_hierarchy_lock.as_ref().unwrap().mek);
 
         println!("💾 Saving vault with updated settings");
-        state.storage.save_vault(manager).await?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
 
         println!("Auth Service Key Derivation:\n{:?}", manager.auth_service.key_derivation());
         println!("Vault Manager Key Derivation:\n{:?}", manager.key_derivation_settings);
@@ -148,4 +291,173 @@ pub async fn update_key_derivation(
         println!("✅ Key derivation update complete");
         Ok(())
     })
-}
\ No newline at end of file
+}
+
+// Lets the login screen show remaining attempts and any active backoff
+#[tauri::command]
+pub async fn get_unlock_status(
+    state: State<'_, VaultState>
+) -> Result<UnlockThrottleStatus, AppError> {
+    let throttle = state.storage.load_unlock_throttle().await?;
+    Ok(throttle.status(Utc::now()))
+}
+
+#[tauri::command]
+pub async fn get_unlock_policy(
+    state: State<'_, VaultState>
+) -> Result<UnlockPolicy, AppError> {
+    protected_command!(state, {
+        let vault_manager_lock = state.vault_manager.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        Ok(manager.unlock_policy.clone())
+    })
+}
+
+#[tauri::command]
+pub async fn update_unlock_policy(
+    state: State<'_, VaultState>,
+    policy: UnlockPolicy
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        manager.update_unlock_policy(policy)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+        sync_unlock_policy(&state, &manager.unlock_policy).await?;
+        Ok(())
+    })
+}
+
+// Minimum strength for the next master password; the current one isn't re-checked
+#[tauri::command]
+pub async fn get_password_policy(state: State<'_, VaultState>) -> Result<PasswordPolicy, AppError> {
+    protected_command!(state, {
+        let vault_manager_lock = state.vault_manager.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        Ok(manager.password_policy)
+    })
+}
+
+#[tauri::command]
+pub async fn update_password_policy(
+    state: State<'_, VaultState>,
+    policy: PasswordPolicy
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        manager.update_password_policy(policy)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn get_auto_lock_settings(state: State<'_, VaultState>) -> Result<AutoLockSettings, AppError> {
+    protected_command!(state, {
+        let vault_manager_lock = state.vault_manager.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        Ok(manager.auto_lock_settings)
+    })
+}
+
+#[tauri::command]
+pub async fn update_auto_lock_settings(
+    state: State<'_, VaultState>,
+    settings: AutoLockSettings
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        manager.update_auto_lock_settings(settings)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+
+        // Takes effect for the running session too
+        state.session.configure(settings);
+        Ok(())
+    })
+}
+
+/// Wraps the current keys under a short PIN so the vault can be reopened without the master password
+#[tauri::command]
+pub async fn enable_quick_unlock(
+    state: State<'_, VaultState>,
+    pin: String
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let mut quick_unlock_lock = state.quick_unlock.write().await;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        let quick_unlock = QuickUnlock::enable(SecureMemory::new(pin), key_hierarchy)?;
+        quick_unlock_lock.replace(quick_unlock);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn disable_quick_unlock(state: State<'_, VaultState>) -> Result<(), AppError> {
+    protected_command!(state, {
+        state.clear_quick_unlock().await;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn quick_unlock(
+    state: State<'_, VaultState>,
+    pin: String
+) -> Result<(), AppError> {
+    if state.is_session_active().await {
+        return Err(AuthError::VaultUnlocked.into());
+    }
+
+    let mut vault_manager_lock = state.vault_manager.write().await;
+    let mut key_hierarchy_lock = state.key_hierarchy.write().await;
+    let mut quick_unlock_lock = state.quick_unlock.write().await;
+
+    let quick_unlock = quick_unlock_lock.as_mut().ok_or(AuthError::QuickUnlockUnavailable)?;
+    let key_hierarchy = match quick_unlock.unlock(SecureMemory::new(pin)) {
+        Ok(key_hierarchy) => key_hierarchy,
+        Err(e) => {
+            // Out of attempts: forget the wrapped keys so only the master password works
+            if quick_unlock.is_exhausted() {
+                quick_unlock_lock.take();
+            }
+            return Err(e.into());
+        }
+    };
+
+    let mut loaded_manager = state.storage.load_vault().await?;
+    if let Err(e) = loaded_manager.login_with_key_hierarchy(&key_hierarchy) {
+        quick_unlock_lock.take();
+        return Err(match e {
+            VaultError::AuthError(auth_err) => auth_err.into(),
+            e => e.into(),
+        });
+    }
+
+    state.storage.save_vault(&mut loaded_manager, &key_hierarchy).await?;
+
+    let auto_lock_settings = loaded_manager.auto_lock_settings;
+    vault_manager_lock.replace(loaded_manager);
+    key_hierarchy_lock.replace(key_hierarchy);
+    state.start_session(auto_lock_settings).await;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_quick_unlock_status(state: State<'_, VaultState>) -> Result<QuickUnlockStatus, AppError> {
+    let quick_unlock_lock = state.quick_unlock.read().await;
+    Ok(quick_unlock_lock
+        .as_ref()
+        .map(|quick_unlock| quick_unlock.status())
+        .unwrap_or(QuickUnlockStatus { enabled: false, remaining_attempts: 0 }))
+}
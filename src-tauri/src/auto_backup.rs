@@ -0,0 +1,62 @@
+use std::time::Duration;
+use chrono::Utc;
+use password_manager_backend::{
+    _vault::{vault_manager::VaultManager, vault_storage::VaultStorage},
+    crypto::KeyHierarchy,
+    error::app_error::AppError,
+};
+use tauri::{AppHandle, Manager};
+
+use crate::VaultState;
+
+// Backup intervals are whole hours, so checking once a minute is plenty
+const MONITOR_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Backs the vault up before a change that can't be undone, such as re-keying it or deleting a category.
+/// Skipped when turned off or when the latest backup already holds the same contents.
+pub async fn snapshot_before_change(
+    storage: &VaultStorage,
+    manager: &mut VaultManager,
+    key_hierarchy: &KeyHierarchy,
+) -> Result<(), AppError> {
+    if !manager.snapshot_due(key_hierarchy) {
+        return Ok(());
+    }
+
+    println!("Taking a backup before a destructive change");
+    storage.create_backup(manager, key_hierarchy).await
+}
+
+/// Background task that backs the unlocked vault up once the configured interval has passed,
+/// as long as something changed since the last backup
+pub fn spawn_auto_backup_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(MONITOR_INTERVAL).await;
+
+            let state = app.state::<VaultState>();
+            if !state.is_session_active().await {
+                continue;
+            }
+
+            let mut vault_manager_lock = state.vault_manager.write().await;
+            let key_hierarchy_lock = state.key_hierarchy.read().await;
+            let (Some(manager), Some(key_hierarchy)) = (vault_manager_lock.as_mut(), key_hierarchy_lock.as_ref()) else {
+                continue;
+            };
+            if !manager.auto_backup_due(key_hierarchy, Utc::now()) {
+                continue;
+            }
+
+            println!("Creating scheduled backup");
+            // The save records the backup time in the vault itself
+            let result = match state.storage.create_backup(manager, key_hierarchy).await {
+                Ok(()) => state.storage.save_vault(manager, key_hierarchy).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                println!("Scheduled backup failed: {:?}", e);
+            }
+        }
+    });
+}
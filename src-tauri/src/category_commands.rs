@@ -1,4 +1,4 @@
-use crate::{protected_command, VaultState};
+use crate::{auto_backup, protected_command, VaultState};
 use password_manager_backend::{category_favorite::category::Category, error::app_error::AppError};
 use uuid::Uuid;
 use tauri::State;
@@ -10,9 +10,11 @@ pub async fn create_category(
 ) -> Result<(), AppError> {
     protected_command!(state, {
         let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
         let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
         let _ = manager.entry_collection.categories.create_category(name)?;
-        state.storage.save_vault(manager).await?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
         Ok(())
     })
 }
@@ -25,13 +27,15 @@ pub async fn rename_category(
 ) -> Result<(), AppError> {
     protected_command!(state, {
         let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
         let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
         manager.entry_collection.categories.rename_category(
             id,
             new_name,
             &mut manager.entry_collection.entries
         )?;
-        state.storage.save_vault(manager).await?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
         Ok(())
     })
 }
@@ -43,12 +47,15 @@ pub async fn delete_category(
 ) -> Result<(), AppError> {
     protected_command!(state, {
         let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
         let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        auto_backup::snapshot_before_change(&state.storage, manager, key_hierarchy).await?;
         manager.entry_collection.categories.delete_category(
             &id,
             &mut manager.entry_collection.entries
         )?;
-        state.storage.save_vault(manager).await?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
         Ok(())
     })
 }
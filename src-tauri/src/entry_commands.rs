@@ -1,6 +1,8 @@
 use crate::{protected_command, VaultState};
+use password_manager_backend::crypto::SecureMemory;
 use uuid::Uuid;
-use password_manager_backend::{entry::{collection::EnhancedSearchResults, entry::DecryptedEntry, overview::EntryData, search::SearchQuery}, error::app_error::AppError};
+use std::path::PathBuf;
+use password_manager_backend::{entry::{attachment::{self, AttachmentInfo}, collection::EnhancedSearchResults, entry::DecryptedEntry, history::DecryptedHistoryRecord, overview::EntryData, search::SearchQuery, totp::TotpCode}, error::app_error::AppError};
 use tauri::State;
 
 #[tauri::command]
@@ -14,7 +16,7 @@ pub async fn create_entry(
         let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
         let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
         manager.entry_collection.create_entry(entry, key_hierarchy)?;
-        state.storage.save_vault(manager).await?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
         Ok(())
     })
 }
@@ -31,7 +33,7 @@ pub async fn update_entry(
         let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
         let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
         manager.entry_collection.update_entry(&id, entry, key_hierarchy)?;
-        state.storage.save_vault(manager).await?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
         Ok(())
     })
 }
@@ -43,9 +45,99 @@ pub async fn delete_entry(
 ) -> Result<(), AppError> {
     protected_command!(state, {
         let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
         let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        let attachment_ids = manager.entry_collection
+            .entries
+            .get(&id)
+            .map(|entry| entry.attachment_ids())
+            .unwrap_or_default();
         manager.entry_collection.delete_entry(&id)?;
-        state.storage.save_vault(manager).await?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+        // Files are only removed once the vault no longer references them
+        state.storage.remove_attachments(&attachment_ids).await?;
+        Ok(())
+    })
+}
+
+// Reads a file from disk, encrypts it and attaches it to the entry
+#[tauri::command]
+pub async fn add_attachment(
+    state: State<'_, VaultState>,
+    id: Uuid,
+    file_path: String,
+    mime_type: Option<String>,
+) -> Result<AttachmentInfo, AppError> {
+    protected_command!(state, {
+        let file_path = PathBuf::from(file_path);
+        let file_name = file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| AppError::PathNotFound(file_path.to_string_lossy().to_string()))?;
+        // Checked before reading so picking a huge file can't exhaust memory
+        attachment::check_size(tokio::fs::metadata(&file_path).await?.len())?;
+        let content = SecureMemory::new(tokio::fs::read(&file_path).await?);
+
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        let (info, ciphertext) = manager.entry_collection.add_attachment(
+            &id, &file_name, mime_type, content.as_ref(), key_hierarchy
+        )?;
+
+        // The file goes down first so the saved vault never references a missing attachment
+        let saved = async {
+            state.storage.write_attachment(&info.id, &ciphertext).await?;
+            state.storage.save_vault(manager, key_hierarchy).await
+        }.await;
+        if let Err(e) = saved {
+            manager.entry_collection.remove_attachment(&id, &info.id)?;
+            state.storage.remove_attachments(&[info.id]).await?;
+            return Err(e);
+        }
+
+        Ok(info)
+    })
+}
+
+// Decrypts an attachment and writes it to a location the user picked
+#[tauri::command]
+pub async fn save_attachment(
+    state: State<'_, VaultState>,
+    id: Uuid,
+    attachment_id: Uuid,
+    destination_path: String,
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        let vault_manager_lock = state.vault_manager.read().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        let ciphertext = state.storage.read_attachment(&attachment_id).await?;
+        let content = manager.entry_collection.open_attachment(&id, &attachment_id, &ciphertext, key_hierarchy)?;
+        tokio::fs::write(PathBuf::from(destination_path), content.as_ref()).await?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn delete_attachment(
+    state: State<'_, VaultState>,
+    id: Uuid,
+    attachment_id: Uuid,
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        manager.entry_collection.remove_attachment(&id, &attachment_id)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+        state.storage.remove_attachments(&[attachment_id]).await?;
         Ok(())
     })
 }
@@ -66,6 +158,72 @@ pub async fn get_decrypted_entry(
     })
 }
 
+// Codes are computed on every call so the frontend only needs to poll when `secondsRemaining` runs out
+#[tauri::command]
+pub async fn get_totp_code(
+    state: State<'_, VaultState>,
+    id: Uuid,
+) -> Result<TotpCode, AppError> {
+    protected_command!(state, {
+        let vault_manager_lock = state.vault_manager.read().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        let code = manager.entry_collection.get_totp_code(&id, key_hierarchy)?;
+        Ok(code)
+    })
+}
+
+// Previous passwords and notes of an entry, decrypted only when the user opens the history
+#[tauri::command]
+pub async fn get_entry_history(
+    state: State<'_, VaultState>,
+    id: Uuid,
+) -> Result<Vec<DecryptedHistoryRecord>, AppError> {
+    protected_command!(state, {
+        let vault_manager_lock = state.vault_manager.read().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        let history = manager.entry_collection.get_entry_history(&id, key_hierarchy)?;
+        Ok(history)
+    })
+}
+
+#[tauri::command]
+pub async fn restore_entry_history(
+    state: State<'_, VaultState>,
+    id: Uuid,
+    version_id: Uuid,
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        manager.entry_collection.restore_entry_history(&id, &version_id)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+        Ok(())
+    })
+}
+
+// Regenerating this entry's password follows the profile's rules; `None` detaches it
+#[tauri::command]
+pub async fn set_entry_generator_profile(
+    state: State<'_, VaultState>,
+    id: Uuid,
+    profile_id: Option<Uuid>,
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        manager.entry_collection.set_entry_generator_profile(&id, profile_id)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+        Ok(())
+    })
+}
 
 // This becomes our primary command for retrieving entries and their metadata
 #[tauri::command]
@@ -1,19 +1,22 @@
-use password_manager_backend::{entry::overview::EntryOverview, error::app_error::AppError};
+use std::path::Path;
+use password_manager_backend::{
+    audit::{breach::BreachList, strength::PasswordPolicy},
+    entry::overview::EntryOverview,
+    error::app_error::AppError,
+};
 use serde::Serialize;
 
 const MIN_PASSWORD_LENGTH: usize = 8;
 const MAX_PASSWORD_LENGTH: usize = 256;
 
-fn check_password_complexity(password: &str) -> bool {
-    let has_uppercase = password.chars().any(|c| c.is_uppercase());
-    let has_lowercase = password.chars().any(|c| c.is_lowercase());
-    let has_number = password.chars().any(|c| c.is_numeric());
-    let has_special = password.chars().any(|c| !c.is_alphanumeric());
-
-    has_uppercase && has_lowercase && has_number && has_special
-}
-
-pub fn validate_password(password: &str, confirm_password: &str) -> Result<(), AppError> {
+// The password must reach the policy's estimated strength. When `breach_list_path` points at a
+// downloaded breached password list, it must not be in it either.
+pub fn validate_password(
+    password: &str,
+    confirm_password: &str,
+    policy: &PasswordPolicy,
+    breach_list_path: Option<&str>,
+) -> Result<(), AppError> {
     if password != confirm_password {
         return Err(AppError::PasswordsDoNotMatch);
     }
@@ -26,8 +29,13 @@ pub fn validate_password(password: &str, confirm_password: &str) -> Result<(), A
         return Err(AppError::PasswordTooShort);
     }
 
-    if !check_password_complexity(password) {
-        return Err(AppError::PasswordTooWeak);
+    policy.check(password)?;
+
+    if let Some(path) = breach_list_path {
+        let occurrences = BreachList::open(Path::new(path))?.occurrences(password)?;
+        if occurrences > 0 {
+            return Err(AppError::PasswordCompromised(occurrences));
+        }
     }
 
     Ok(())
@@ -48,6 +56,8 @@ pub struct VaultPaths {
     pub backup_dir: String,
     #[serde(rename = "tempDir")]
     pub temp_dir: String,
+    #[serde(rename = "attachmentsDir")]
+    pub attachments_dir: String,
 }
 
 
@@ -62,6 +72,8 @@ macro_rules! protected_command {
         if !$state.is_session_active().await {
             return Err(AppError::VaultLocked);
         }
+        // Any vault command counts as activity for the auto-lock timer
+        $state.session.touch();
         $action
     }};
 }
\ No newline at end of file
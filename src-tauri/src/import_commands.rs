@@ -0,0 +1,45 @@
+use crate::{protected_command, VaultState};
+use std::path::PathBuf;
+use password_manager_backend::{
+    error::{app_error::AppError, import_error::ImportError},
+    import::{ImportFormat, ImportPlan, ImportReport},
+};
+use tauri::State;
+
+// Reads an export from another password manager. With `dry_run` set nothing is written and the
+// report only shows what would be imported; otherwise every valid row is added with a single save.
+#[tauri::command]
+pub async fn import_entries(
+    state: State<'_, VaultState>,
+    file_path: String,
+    format: Option<ImportFormat>,
+    dry_run: bool,
+) -> Result<ImportReport, AppError> {
+    protected_command!(state, {
+        let file_path = PathBuf::from(file_path);
+        if !file_path.exists() {
+            return Err(AppError::PathNotFound(file_path.to_string_lossy().to_string()));
+        }
+        let content = String::from_utf8(tokio::fs::read(&file_path).await?)
+            .map_err(|_| ImportError::Malformed("file is not valid UTF-8".to_string()))?;
+
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        let plan = ImportPlan::prepare(&content, format, &manager.entry_collection)?;
+        if dry_run {
+            return Ok(plan.report().clone());
+        }
+
+        let (report, batch) = plan.commit(&mut manager.entry_collection, key_hierarchy)?;
+        if let Err(e) = state.storage.save_vault(manager, key_hierarchy).await {
+            batch.revert(&mut manager.entry_collection);
+            return Err(e);
+        }
+
+        println!("Imported {} entries from {:?}", report.imported, report.format);
+        Ok(report)
+    })
+}
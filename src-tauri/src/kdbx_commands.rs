@@ -0,0 +1,69 @@
+use crate::{helpers, protected_command, VaultState};
+use std::path::PathBuf;
+use password_manager_backend::{
+    audit::strength::PasswordPolicy,
+    error::app_error::AppError,
+    import::{ImportPlan, ImportReport},
+    kdbx,
+};
+use tauri::State;
+
+// Imports a KeePass database the same way as other exports, including the dry run
+#[tauri::command]
+pub async fn import_kdbx(
+    state: State<'_, VaultState>,
+    file_path: String,
+    password: String,
+    dry_run: bool,
+) -> Result<ImportReport, AppError> {
+    protected_command!(state, {
+        let file_path = PathBuf::from(file_path);
+        if !file_path.exists() {
+            return Err(AppError::PathNotFound(file_path.to_string_lossy().to_string()));
+        }
+        let data = tokio::fs::read(&file_path).await?;
+
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        let plan = ImportPlan::prepare_kdbx(&data, &password, &manager.entry_collection)?;
+        if dry_run {
+            return Ok(plan.report().clone());
+        }
+
+        let (report, batch) = plan.commit(&mut manager.entry_collection, key_hierarchy)?;
+        if let Err(e) = state.storage.save_vault(manager, key_hierarchy).await {
+            batch.revert(&mut manager.entry_collection);
+            return Err(e);
+        }
+
+        println!("Imported {} entries from a KeePass database", report.imported);
+        Ok(report)
+    })
+}
+
+// Writes the vault to a KDBX 4 database under a password chosen for the export
+#[tauri::command]
+pub async fn export_kdbx(
+    state: State<'_, VaultState>,
+    destination_path: String,
+    password: String,
+    confirm_password: String,
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        helpers::validate_password(&password, &confirm_password, &PasswordPolicy::default(), None)?;
+
+        let vault_manager_lock = state.vault_manager.read().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        let data = kdbx::writer::export(&manager.entry_collection, key_hierarchy, &password)?;
+        tokio::fs::write(PathBuf::from(destination_path), data).await?;
+
+        println!("Exported {} entries to a KeePass database", manager.entry_collection.entries.len());
+        Ok(())
+    })
+}
@@ -1,20 +1,35 @@
 mod helpers;
+mod auto_backup;
 mod authentication_commands;
 mod entry_commands;
 mod category_commands;
+mod import_commands;
+mod kdbx_commands;
+mod portable_commands;
+mod audit_commands;
+mod session;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use helpers::VaultPaths;
 use password_manager_backend::{
     _vault::{
+        auto_lock::AutoLockSettings,
+        backup::{AutoBackupSettings, BackupInfo, BackupRetentionPolicy, RestorePreview},
+        merge::{MergePlan, MergeReport, MergeResolution},
         password_generation::PasswordGeneratorSettings, 
+        password_rules::{GeneratorProfile, PasswordRules},
         vault_manager::VaultManager, 
         vault_storage::VaultStorage
     }, 
+    audit::strength::PasswordPolicy,
+    auth::quick_unlock::QuickUnlock,
     crypto::{KeyDerivation, KeyHierarchy, SecureMemory}, 
     error::app_error::AppError
 };
+use session::SessionManager;
 use tauri::{async_runtime::RwLock, State};
+use uuid::Uuid;
 
 // State Management
 #[derive(Default)]
@@ -23,6 +38,9 @@ pub struct VaultState {
     storage: VaultStorage,
     key_hierarchy: Arc<RwLock<Option<KeyHierarchy>>>,
     session_active: Arc<RwLock<bool>>,
+    session: SessionManager,
+    // PIN-wrapped keys kept in memory only; survives auto-lock but not logout or a restart
+    quick_unlock: Arc<RwLock<Option<QuickUnlock>>>,
 }
 
 impl VaultState {
@@ -34,6 +52,17 @@ impl VaultState {
         let mut session = self.session_active.write().await;
         *session = active;
     }
+
+    /// Marks the session active and starts the auto-lock timer with the vault's settings
+    pub async fn start_session(&self, settings: AutoLockSettings) {
+        self.session.start(settings);
+        self.set_session_active(true).await;
+    }
+
+    /// Drops the PIN-wrapped keys, e.g. after logout or when the master key changes
+    pub async fn clear_quick_unlock(&self) {
+        self.quick_unlock.write().await.take();
+    }
 }
 
 #[derive(serde::Serialize)]
@@ -70,6 +99,7 @@ async fn get_vault_paths(
             vault_path: storage.get_vault_path().to_string_lossy().to_string(),
             backup_dir: storage.get_backup_dir().to_string_lossy().to_string(),
             temp_dir: storage.get_temp_dir().to_string_lossy().to_string(),
+            attachments_dir: storage.get_attachments_dir().to_string_lossy().to_string(),
         })
     })
 }
@@ -78,22 +108,23 @@ async fn get_vault_paths(
 async fn initialize_app(
     state: State<'_, VaultState>, 
     password: String, 
-    confirm_password: String
+    confirm_password: String,
+    breach_list_path: Option<String>,
 ) -> Result<(), AppError> {
     if state.storage.vault_path.join("vault.dat").exists() {
         return Err(AppError::VaultAlreadyExists);
     }
 
-    helpers::validate_password(&password, &confirm_password)?;
+    helpers::validate_password(&password, &confirm_password, &PasswordPolicy::default(), breach_list_path.as_deref())?;
 
-    let (manager, key_hierarchy) = VaultManager::new(SecureMemory::new(password))?;
+    let (mut manager, key_hierarchy) = VaultManager::new(SecureMemory::new(password))?;
     
     let mut vault_manager_lock = state.vault_manager.write().await;
     let mut key_hierarchy_lock = state.key_hierarchy.write().await;
 
-    state.storage.save_vault(&manager).await?;
+    state.storage.save_vault(&mut manager, &key_hierarchy).await?;
 
-    state.set_session_active(true).await;
+    state.start_session(manager.auto_lock_settings).await;
 
     // Update both states while holding the locks
     key_hierarchy_lock.replace(key_hierarchy);
@@ -120,32 +151,180 @@ async fn create_backup(
 ) -> Result<(), AppError> {
     protected_command!(state, {
         let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
         let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
 
-        state.storage.create_backup(manager).await?;
-        state.storage.save_vault(manager).await?;
+        state.storage.create_backup(manager, key_hierarchy).await?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
         
         Ok(())
     })
 }
 
+// Unlocks the backup with its own master password and compares it to the current vault. A dry run
+// only returns the comparison; otherwise the current vault is backed up and the session continues
+// in the restored one.
 #[tauri::command]
 async fn restore_from_backup(
     state: State<'_, VaultState>,
     backup_path: String,
+    master_password: String,
+    dry_run: bool,
+) -> Result<RestorePreview, AppError> {
+    protected_command!(state, {
+        let backup_path = std::path::PathBuf::from(backup_path);
+
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let mut key_hierarchy_lock = state.key_hierarchy.write().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        // Not counted by the unlock throttle: the session is already unlocked, so guessing
+        // an old backup's password gains nothing the current vault doesn't already show
+        let (mut restored_manager, restored_key_hierarchy) = state.storage
+            .open_backup(&backup_path, SecureMemory::new(master_password))
+            .await?;
+
+        let preview = RestorePreview::compare(&manager.entry_collection, &restored_manager.entry_collection);
+        if dry_run {
+            return Ok(preview);
+        }
+
+        // Always taken, whatever the automatic backup settings say, so the restore can be undone.
+        // Pruning is skipped so retention can't delete the backup being restored.
+        state.storage.create_backup_without_pruning(manager, key_hierarchy).await?;
+
+        // restore_from_file brings back the backup's attachments; the save then signs it with the current format
+        state.storage.restore_from_file(backup_path).await?;
+        state.storage.save_vault(&mut restored_manager, &restored_key_hierarchy).await?;
+        state.storage.remove_orphaned_attachments(&restored_manager.entry_collection.attachment_ids()).await?;
+        authentication_commands::sync_unlock_policy(&state, &restored_manager.unlock_policy).await?;
+
+        // The backup may use an older master password than the one the PIN wraps
+        state.clear_quick_unlock().await;
+
+        let auto_lock_settings = restored_manager.auto_lock_settings;
+        vault_manager_lock.replace(restored_manager);
+        key_hierarchy_lock.replace(restored_key_hierarchy);
+        state.start_session(auto_lock_settings).await;
+
+        Ok(preview)
+    })
+}
+
+// Brings changes from another copy of this vault, e.g. one edited on a different machine or an
+// older backup, into the current one. A dry run reports what would change and which entries
+// conflict; committing needs a resolution for every conflict.
+#[tauri::command]
+async fn merge_vault_file(
+    state: State<'_, VaultState>,
+    file_path: String,
+    master_password: String,
+    resolutions: HashMap<Uuid, MergeResolution>,
+    dry_run: bool,
+) -> Result<MergeReport, AppError> {
+    protected_command!(state, {
+        let file_path = std::path::PathBuf::from(file_path);
+
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        let (other_manager, other_key_hierarchy) = state.storage
+            .open_backup(&file_path, SecureMemory::new(master_password))
+            .await?;
+        let plan = MergePlan::prepare(manager, key_hierarchy, other_manager, &other_key_hierarchy)?;
+        if dry_run {
+            return Ok(plan.report().clone());
+        }
+        plan.ensure_resolved(&resolutions)?;
+
+        auto_backup::snapshot_before_change(&state.storage, manager, key_hierarchy).await?;
+
+        let incoming_attachments = plan.incoming_attachment_ids(&resolutions);
+        let missing_attachments = state.storage.import_attachments_from(&file_path, &incoming_attachments).await?;
+        let report = plan.commit(&mut manager.entry_collection, &resolutions, &missing_attachments)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+
+        // Local copies that were replaced may have had attachments of their own
+        state.storage.remove_orphaned_attachments(&manager.entry_collection.attachment_ids()).await?;
+        Ok(report)
+    })
+}
+
+#[tauri::command]
+async fn list_backups(
+    state: State<'_, VaultState>,
+) -> Result<Vec<BackupInfo>, AppError> {
+    protected_command!(state, {
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        state.storage.list_backups(key_hierarchy).await
+    })
+}
+
+#[tauri::command]
+async fn delete_backup(
+    state: State<'_, VaultState>,
+    file_name: String,
 ) -> Result<(), AppError> {
-    let backup_path = std::path::PathBuf::from(backup_path);
-    let restored_vault = state.storage.restore_from_file(backup_path).await?;
+    protected_command!(state, {
+        state.storage.delete_backup(&file_name).await
+    })
+}
 
-    // Save the restored vault to the state
-    let mut vault_manager_lock = state.vault_manager.write().await;
-    vault_manager_lock.replace(restored_vault);
+#[tauri::command]
+async fn get_backup_retention(state: State<'_, VaultState>) -> Result<BackupRetentionPolicy, AppError> {
+    protected_command!(state, {
+        let vault_manager_lock = state.vault_manager.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        Ok(manager.backup_retention)
+    })
+}
 
-    let new_manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+// The new policy is applied the next time a backup is created
+#[tauri::command]
+async fn update_backup_retention(
+    state: State<'_, VaultState>,
+    policy: BackupRetentionPolicy,
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        manager.update_backup_retention(policy)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+        Ok(())
+    })
+}
 
-    state.storage.save_vault(new_manager).await?;
+#[tauri::command]
+async fn get_auto_backup_settings(state: State<'_, VaultState>) -> Result<AutoBackupSettings, AppError> {
+    protected_command!(state, {
+        let vault_manager_lock = state.vault_manager.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        Ok(manager.auto_backup_settings)
+    })
+}
 
-    Ok(())
+// Picked up by the backup monitor on its next check
+#[tauri::command]
+async fn update_auto_backup_settings(
+    state: State<'_, VaultState>,
+    settings: AutoBackupSettings,
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        manager.update_auto_backup_settings(settings)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+        Ok(())
+    })
 }
 
 // Password Generator Commands
@@ -165,23 +344,89 @@ async fn update_password_generator_settings(
 ) -> Result<(), AppError> {
     protected_command!(state, {
         let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
         let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
         manager.password_generator_settings.configure(settings)?;
-        state.storage.save_vault(manager).await?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
         Ok(())
     })
 }
 
+// A profile given explicitly wins; otherwise regenerating an entry's password follows the profile
+// attached to that entry, if any.
 #[tauri::command]
-async fn generate_password(state: State<'_, VaultState>) -> Result<String, AppError> {
+async fn generate_password(
+    state: State<'_, VaultState>,
+    profile_id: Option<Uuid>,
+    entry_id: Option<Uuid>,
+) -> Result<String, AppError> {
     protected_command!(state, {
         let vault_manager_lock = state.vault_manager.read().await;
         let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
-        let password = manager.password_generator_settings.generate()?;
+        let collection = &manager.entry_collection;
+
+        let profile = match (profile_id, entry_id) {
+            (Some(profile_id), _) => Some(collection.generator_profiles.get(&profile_id)?),
+            (None, Some(entry_id)) => collection.entry_generator_profile(&entry_id)?,
+            (None, None) => None,
+        };
+        let password = match profile {
+            Some(profile) => manager.password_generator_settings.generate_with_rules(&profile.rules)?,
+            None => manager.password_generator_settings.generate()?,
+        };
         Ok(password)
     })
 }
 
+#[tauri::command]
+async fn list_generator_profiles(state: State<'_, VaultState>) -> Result<Vec<GeneratorProfile>, AppError> {
+    protected_command!(state, {
+        let vault_manager_lock = state.vault_manager.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        Ok(manager.entry_collection.generator_profiles.list())
+    })
+}
+
+// Creates a profile, or replaces the rules and name of an existing one when `id` is given
+#[tauri::command]
+async fn save_generator_profile(
+    state: State<'_, VaultState>,
+    id: Option<Uuid>,
+    name: String,
+    rules: PasswordRules,
+) -> Result<Uuid, AppError> {
+    protected_command!(state, {
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        let id = manager.entry_collection.generator_profiles.save(id, name, rules)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+        Ok(id)
+    })
+}
+
+// Entries using the profile fall back to the plain generator settings
+#[tauri::command]
+async fn delete_generator_profile(state: State<'_, VaultState>, id: Uuid) -> Result<(), AppError> {
+    protected_command!(state, {
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+        manager.entry_collection.delete_generator_profile(&id)?;
+        state.storage.save_vault(manager, key_hierarchy).await?;
+        Ok(())
+    })
+}
+
+// Converts a site's Apple `passwordrules` attribute into rules the profile editor can show and save
+#[tauri::command]
+async fn parse_password_rules(rules: String) -> Result<PasswordRules, AppError> {
+    Ok(PasswordRules::parse_apple(&rules)?)
+}
+
 
 #[tauri::command]
 async fn calculate_password_entropy(state: State<'_, VaultState>) -> Result<f64, AppError> {
@@ -207,10 +452,13 @@ pub fn run() {
         .manage(VaultState::default())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
-        .setup(|_app| {
+        .setup(|app| {
+            session::spawn_auto_lock_monitor(app.handle().clone());
+            auto_backup::spawn_auto_backup_monitor(app.handle().clone());
+
             #[cfg(debug_assertions)]
             {
-                let window = tauri::Manager::get_webview_window(_app, "main").unwrap();
+                let window = tauri::Manager::get_webview_window(app, "main").unwrap();
                 window.open_devtools();
             }
             Ok(())
@@ -225,31 +473,72 @@ pub fn run() {
             get_key_derivation_settings,
             create_backup,
             restore_from_backup,
+            merge_vault_file,
+            list_backups,
+            delete_backup,
+            get_backup_retention,
+            update_backup_retention,
+            get_auto_backup_settings,
+            update_auto_backup_settings,
             
             // Password Generator
             get_password_generator_settings,
             update_password_generator_settings,
             generate_password,
             calculate_password_entropy,
+            list_generator_profiles,
+            save_generator_profile,
+            delete_generator_profile,
+            parse_password_rules,
             
             // Authentication Commands
             authentication_commands::login,
             authentication_commands::logout,
             authentication_commands::change_master_password,
             authentication_commands::update_key_derivation,
+            authentication_commands::get_unlock_status,
+            authentication_commands::get_unlock_policy,
+            authentication_commands::update_unlock_policy,
+            authentication_commands::get_password_policy,
+            authentication_commands::update_password_policy,
+            authentication_commands::restore_verified_backup,
+            authentication_commands::get_auto_lock_settings,
+            authentication_commands::update_auto_lock_settings,
+            authentication_commands::enable_quick_unlock,
+            authentication_commands::disable_quick_unlock,
+            authentication_commands::quick_unlock,
+            authentication_commands::get_quick_unlock_status,
             
             // Entry Commands
             entry_commands::create_entry,            
             entry_commands::update_entry,            
             entry_commands::delete_entry,            
+            entry_commands::add_attachment,
+            entry_commands::save_attachment,
+            entry_commands::delete_attachment,
             entry_commands::get_decrypted_entry,      
-            entry_commands::search_entries,          
+            entry_commands::search_entries,
+            entry_commands::get_totp_code,
+            entry_commands::get_entry_history,
+            entry_commands::restore_entry_history,
+            entry_commands::set_entry_generator_profile,          
 
             // Category Commands
             category_commands::create_category,
             category_commands::rename_category,
             category_commands::delete_category,
             category_commands::search_categories,
+
+            // Import Commands
+            import_commands::import_entries,
+            kdbx_commands::import_kdbx,
+            kdbx_commands::export_kdbx,
+            portable_commands::export_portable_vault,
+            portable_commands::import_portable_vault,
+
+            // Security Audit
+            audit_commands::run_security_audit,
+            audit_commands::estimate_password_strength,
             
             // Vault Status
             get_vault_status,
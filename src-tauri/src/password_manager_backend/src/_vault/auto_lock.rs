@@ -0,0 +1,45 @@
+// password_manager_backend/src/_vault/auto_lock.rs
+
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::_vault::{DEFAULT_IDLE_TIMEOUT_MINUTES, MAX_IDLE_TIMEOUT_MINUTES};
+use crate::error::vault_error::VaultError;
+
+/// When an unlocked vault should lock itself again.
+/// Enforced by the app's session manager; stored here so it survives restarts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoLockSettings {
+    // Minutes without a vault command before locking; `None` disables the idle timer
+    pub idle_timeout_minutes: Option<u32>,
+    // Lock as soon as a system sleep/suspend is detected
+    pub lock_on_suspend: bool,
+}
+
+impl Default for AutoLockSettings {
+    fn default() -> Self {
+        Self {
+            idle_timeout_minutes: Some(DEFAULT_IDLE_TIMEOUT_MINUTES),
+            lock_on_suspend: true,
+        }
+    }
+}
+
+impl AutoLockSettings {
+    pub fn validate(&self) -> Result<(), VaultError> {
+        match self.idle_timeout_minutes {
+            Some(0) => Err(VaultError::AutoLockError(
+                "Idle timeout must be at least one minute".to_string()
+            )),
+            Some(minutes) if minutes > MAX_IDLE_TIMEOUT_MINUTES => Err(VaultError::AutoLockError(
+                format!("Idle timeout cannot exceed {} minutes", MAX_IDLE_TIMEOUT_MINUTES)
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout_minutes
+            .map(|minutes| Duration::from_secs(u64::from(minutes) * 60))
+    }
+}
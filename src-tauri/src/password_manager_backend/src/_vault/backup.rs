@@ -0,0 +1,189 @@
+// password_manager_backend/src/_vault/backup.rs
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use crate::_vault::{
+    BACKUP_FILE_PREFIX, BACKUP_TIMESTAMP_FORMAT, DEFAULT_AUTO_BACKUP_INTERVAL_HOURS, DEFAULT_KEEP_DAILY_BACKUPS,
+    DEFAULT_KEEP_LAST_BACKUPS, DEFAULT_KEEP_MONTHLY_BACKUPS, DEFAULT_KEEP_WEEKLY_BACKUPS,
+    MAX_AUTO_BACKUP_INTERVAL_HOURS, MAX_BACKUP_RETENTION,
+};
+use crate::entry::collection::EntryCollection;
+use crate::entry::entry::Entry;
+use crate::entry::overview::EntryOverview;
+use crate::error::vault_error::VaultError;
+
+/// A backup file as shown in the backup list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub path: PathBuf,
+    pub created_at: DateTime<Utc>,
+    // Bytes on disk, including the backup's attachment copies
+    pub size: u64,
+    // `None` when the backup can't be decrypted with the current keys, e.g. it predates a key change
+    pub entry_count: Option<usize>,
+    // The file couldn't be read or isn't a vault; listed anyway so it can be deleted
+    pub corrupt: bool,
+}
+
+/// Reads the creation time out of a `vault_backup_<timestamp>.dat` file name
+pub fn parse_backup_file_name(file_name: &str) -> Option<DateTime<Utc>> {
+    let timestamp = file_name
+        .strip_prefix(BACKUP_FILE_PREFIX)?
+        .strip_suffix(".dat")?;
+    NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT)
+        .ok()
+        .map(|timestamp| timestamp.and_utc())
+}
+
+/// What restoring a backup would do to the current entries
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestorePreview {
+    // Only in the backup; restoring brings them back
+    pub added: Vec<EntryOverview>,
+    // Only in the current vault; restoring loses them
+    pub removed: Vec<EntryOverview>,
+    // In both but different; restoring reverts them to the backup's version
+    pub changed: Vec<EntryOverview>,
+    pub unchanged_count: usize,
+}
+
+impl RestorePreview {
+    /// Matches entries by id. Both collections must be unsealed; secrets are compared as ciphertext,
+    /// which stays the same until the entry is edited.
+    pub fn compare(current: &EntryCollection, backup: &EntryCollection) -> Self {
+        let mut preview = Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+            unchanged_count: 0,
+        };
+
+        for (id, entry) in &backup.entries {
+            match current.entries.get(id) {
+                None => preview.added.push(entry.to_overview()),
+                Some(existing) if serde_cbor::to_vec(existing).ok() != serde_cbor::to_vec(entry).ok() => {
+                    preview.changed.push(entry.to_overview())
+                }
+                Some(_) => preview.unchanged_count += 1,
+            }
+        }
+        preview.removed = current.entries
+            .values()
+            .filter(|entry| !backup.entries.contains_key(entry.id()))
+            .map(Entry::to_overview)
+            .collect();
+
+        for list in [&mut preview.added, &mut preview.removed, &mut preview.changed] {
+            list.sort_by_key(|entry| entry.title.to_lowercase());
+        }
+        preview
+    }
+}
+
+/// Which backups survive pruning. A backup is kept if any rule keeps it: the newest `keep_last`,
+/// plus the newest backup of each of the last `keep_daily` days, `keep_weekly` ISO weeks and
+/// `keep_monthly` months that have one. Days and weeks are counted in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupRetentionPolicy {
+    pub keep_last: u32,
+    pub keep_daily: u32,
+    pub keep_weekly: u32,
+    pub keep_monthly: u32,
+}
+
+impl Default for BackupRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: DEFAULT_KEEP_LAST_BACKUPS,
+            keep_daily: DEFAULT_KEEP_DAILY_BACKUPS,
+            keep_weekly: DEFAULT_KEEP_WEEKLY_BACKUPS,
+            keep_monthly: DEFAULT_KEEP_MONTHLY_BACKUPS,
+        }
+    }
+}
+
+impl BackupRetentionPolicy {
+    pub fn validate(&self) -> Result<(), VaultError> {
+        // Pruning runs right after a backup, which must never be the one it deletes
+        if self.keep_last == 0 {
+            return Err(VaultError::BackupError("At least the latest backup must be kept".to_string()));
+        }
+        if [self.keep_last, self.keep_daily, self.keep_weekly, self.keep_monthly]
+            .iter()
+            .any(|count| *count > MAX_BACKUP_RETENTION)
+        {
+            return Err(VaultError::BackupError(
+                format!("Retention counts cannot exceed {}", MAX_BACKUP_RETENTION)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Indices into `created`, which must be sorted newest first, of the backups to delete
+    pub fn expired(&self, created: &[DateTime<Utc>]) -> Vec<usize> {
+        let mut keep: HashSet<usize> = (0..created.len().min(self.keep_last as usize)).collect();
+
+        for (count, bucket_format) in [
+            (self.keep_daily, "%Y-%m-%d"),
+            (self.keep_weekly, "%G-W%V"),
+            (self.keep_monthly, "%Y-%m"),
+        ] {
+            let mut buckets = HashSet::new();
+            for (index, timestamp) in created.iter().enumerate() {
+                if buckets.len() >= count as usize {
+                    break;
+                }
+                // The first backup seen in a bucket is its newest
+                if buckets.insert(timestamp.format(bucket_format).to_string()) {
+                    keep.insert(index);
+                }
+            }
+        }
+
+        (0..created.len()).filter(|index| !keep.contains(index)).collect()
+    }
+}
+
+/// When the vault is backed up without anyone asking.
+/// Enforced by the app's backup monitor; stored here so it survives restarts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoBackupSettings {
+    // Hours between automatic backups of a vault that has changed; `None` turns them off
+    pub interval_hours: Option<u32>,
+    // Back up before changing the master password or key derivation, or deleting a category
+    pub snapshot_before_changes: bool,
+}
+
+impl Default for AutoBackupSettings {
+    fn default() -> Self {
+        Self {
+            interval_hours: Some(DEFAULT_AUTO_BACKUP_INTERVAL_HOURS),
+            snapshot_before_changes: true,
+        }
+    }
+}
+
+impl AutoBackupSettings {
+    pub fn validate(&self) -> Result<(), VaultError> {
+        match self.interval_hours {
+            Some(0) => Err(VaultError::BackupError(
+                "Backup interval must be at least one hour".to_string()
+            )),
+            Some(hours) if hours > MAX_AUTO_BACKUP_INTERVAL_HOURS => Err(VaultError::BackupError(
+                format!("Backup interval cannot exceed {} hours", MAX_AUTO_BACKUP_INTERVAL_HOURS)
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn interval(&self) -> Option<TimeDelta> {
+        self.interval_hours.map(|hours| TimeDelta::hours(i64::from(hours)))
+    }
+}
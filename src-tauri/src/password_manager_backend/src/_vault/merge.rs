@@ -0,0 +1,307 @@
+// password_manager_backend/src/_vault/merge.rs
+
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::_vault::vault_manager::VaultManager;
+use crate::crypto::{constant_time_eq, KeyHierarchy};
+use crate::entry::MAX_HISTORY_RECORDS;
+use crate::entry::collection::EntryCollection;
+use crate::entry::entry::Entry;
+use crate::entry::overview::EntryOverview;
+use crate::error::entry_error::EntryError;
+use crate::error::vault_error::VaultError;
+
+/// Which copy of a conflicting entry survives the merge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeResolution {
+    KeepLocal,
+    KeepOther,
+}
+
+/// An entry changed in both vaults since they last agreed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeConflict {
+    pub id: Uuid,
+    pub local: EntryOverview,
+    pub other: EntryOverview,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeReport {
+    pub dry_run: bool,
+    // Only in the other vault
+    pub added: Vec<EntryOverview>,
+    // Newer in the other vault, replacing the local copy
+    pub updated: Vec<EntryOverview>,
+    // Need a `MergeResolution` before the merge can be committed
+    pub conflicts: Vec<MergeConflict>,
+    // Categories only the other vault had, matched by name
+    pub categories_added: Vec<String>,
+    // Identical in both, or newer here
+    pub unchanged_count: usize,
+    // Attachments of incoming entries whose files weren't found beside the other vault; they are left out
+    pub missing_attachments: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Add,
+    Update,
+    Conflict,
+    KeepLocal,
+}
+
+/// A comparison of the unlocked vault with another copy of it, ready to be committed or reported
+/// as a dry run.
+///
+/// Entries are matched by id. When both copies differ, the entry's history stands in for the
+/// common ancestor: every change to the secrets adds a history record, so a copy holding all of
+/// the other's records and more has seen every change the other made. Such a copy wins as long
+/// as it is also the newer one; otherwise the other side edited something afterwards. Any other
+/// difference is a conflict: with equal histories only public fields differ, and without an
+/// ancestor there is no telling whether one or both sides edited them, so the user decides.
+/// Deletions aren't recorded anywhere, so an entry missing from one side is always kept.
+pub struct MergePlan {
+    other: EntryCollection,
+    outcomes: Vec<(Uuid, Outcome)>,
+    report: MergeReport,
+}
+
+// Whether `entry` has every history record of `ancestor`. Records older than anything a full
+// history still holds may have been trimmed, so they aren't required.
+fn has_history_of(entry: &Entry, ancestor: &Entry) -> bool {
+    let ids: HashSet<Uuid> = entry.history.iter().map(|record| record.id).collect();
+    let trimmed_before = match entry.history.first() {
+        Some(oldest) if entry.history.len() >= MAX_HISTORY_RECORDS => Some(oldest.replaced_at),
+        _ => None,
+    };
+
+    ancestor.history
+        .iter()
+        .filter(|record| trimmed_before.is_none_or(|oldest| record.replaced_at >= oldest))
+        .all(|record| ids.contains(&record.id))
+}
+
+fn compare(local: &Entry, other: &Entry) -> Outcome {
+    if serde_cbor::to_vec(local).ok() == serde_cbor::to_vec(other).ok() {
+        return Outcome::KeepLocal;
+    }
+
+    let local_has_other = has_history_of(local, other);
+    let other_has_local = has_history_of(other, local);
+
+    match (local_has_other, other_has_local) {
+        (true, false) if local.updated_at >= other.updated_at => Outcome::KeepLocal,
+        (false, true) if other.updated_at >= local.updated_at => Outcome::Update,
+        _ => Outcome::Conflict,
+    }
+}
+
+impl MergePlan {
+    /// Compares the unlocked `local` vault with `other`, unlocked with its own password.
+    /// Both must be copies of the same vault so the entries' ciphertexts can move between them.
+    /// Nothing is changed.
+    pub fn prepare(
+        local: &VaultManager,
+        local_key_hierarchy: &KeyHierarchy,
+        other: VaultManager,
+        other_key_hierarchy: &KeyHierarchy,
+    ) -> Result<Self, VaultError> {
+        if local.auth_service.vault_id() != other.auth_service.vault_id()
+            || !constant_time_eq(local_key_hierarchy.mek.as_ref(), other_key_hierarchy.mek.as_ref())
+        {
+            return Err(VaultError::MergeError(
+                "The file is not a copy of this vault; import it instead".to_string()
+            ));
+        }
+
+        let local = &local.entry_collection;
+        let other = other.entry_collection;
+        let mut report = MergeReport {
+            dry_run: true,
+            added: Vec::new(),
+            updated: Vec::new(),
+            conflicts: Vec::new(),
+            categories_added: Vec::new(),
+            unchanged_count: 0,
+            missing_attachments: 0,
+        };
+
+        let mut outcomes = Vec::with_capacity(other.entries.len());
+        for (id, other_entry) in &other.entries {
+            let outcome = match local.entries.get(id) {
+                None => Outcome::Add,
+                Some(local_entry) => compare(local_entry, other_entry),
+            };
+            match outcome {
+                Outcome::Add => report.added.push(other_entry.to_overview()),
+                Outcome::Update => report.updated.push(other_entry.to_overview()),
+                Outcome::Conflict => report.conflicts.push(MergeConflict {
+                    id: *id,
+                    local: local.entries[id].to_overview(),
+                    other: other_entry.to_overview(),
+                }),
+                Outcome::KeepLocal => report.unchanged_count += 1,
+            }
+            outcomes.push((*id, outcome));
+        }
+
+        report.categories_added = other.categories
+            .get_all_categories()
+            .iter()
+            .map(|category| category.name().to_string())
+            .filter(|name| local.categories.get_category_id_by_name(name).is_none())
+            .collect();
+
+        report.added.sort_by_key(|entry| entry.title.to_lowercase());
+        report.updated.sort_by_key(|entry| entry.title.to_lowercase());
+        report.conflicts.sort_by_key(|conflict| conflict.local.title.to_lowercase());
+        report.categories_added.sort_by_key(|name| name.to_lowercase());
+
+        Ok(Self { other, outcomes, report })
+    }
+
+    pub fn report(&self) -> &MergeReport {
+        &self.report
+    }
+
+    // Whether the other vault's copy of `id` replaces or joins the local entries
+    fn takes_other(outcome: Outcome, id: &Uuid, resolutions: &HashMap<Uuid, MergeResolution>) -> bool {
+        match outcome {
+            Outcome::Add | Outcome::Update => true,
+            Outcome::Conflict => resolutions.get(id) == Some(&MergeResolution::KeepOther),
+            Outcome::KeepLocal => false,
+        }
+    }
+
+    /// Fails unless every conflict has a resolution
+    pub fn ensure_resolved(&self, resolutions: &HashMap<Uuid, MergeResolution>) -> Result<(), VaultError> {
+        let unresolved = self.report.conflicts
+            .iter()
+            .filter(|conflict| !resolutions.contains_key(&conflict.id))
+            .count();
+        if unresolved > 0 {
+            return Err(VaultError::MergeError(format!("Conflicts without a resolution: {}", unresolved)));
+        }
+        Ok(())
+    }
+
+    /// Attachments referenced by the entries that will be copied over, so their files can be
+    /// brought along before committing
+    pub fn incoming_attachment_ids(&self, resolutions: &HashMap<Uuid, MergeResolution>) -> Vec<Uuid> {
+        self.outcomes
+            .iter()
+            .filter(|(id, outcome)| Self::takes_other(*outcome, id, resolutions))
+            .filter_map(|(id, _)| self.other.entries.get(id))
+            .flat_map(Entry::attachment_ids)
+            .collect()
+    }
+
+    /// Applies the merge to `local`. Every conflict needs a resolution. Incoming entries drop
+    /// attachments listed in `missing_attachments`; favorites are kept if either copy has one.
+    /// The caller saves the vault afterwards.
+    pub fn commit(
+        mut self,
+        local: &mut EntryCollection,
+        resolutions: &HashMap<Uuid, MergeResolution>,
+        missing_attachments: &HashSet<Uuid>,
+    ) -> Result<MergeReport, VaultError> {
+        self.ensure_resolved(resolutions)?;
+
+        for name in &self.report.categories_added {
+            local.categories.create_category(name.clone()).map_err(EntryError::from)?;
+        }
+
+        for (id, outcome) in &self.outcomes {
+            let local_favorite = local.entries.get(id).is_some_and(|entry| entry.favorite);
+            let local_profile = local.entries.get(id).and_then(|entry| entry.generator_profile_id);
+            let Some(mut incoming) = self.other.entries.remove(id) else { continue };
+
+            if !Self::takes_other(*outcome, id, resolutions) {
+                if incoming.favorite && !local_favorite {
+                    if let Some(entry) = local.entries.get_mut(id) {
+                        entry.favorite = true;
+                        local.favorites.add_favorite(*id);
+                    }
+                }
+                continue;
+            }
+
+            // Category ids differ between the copies, names don't
+            incoming.category_id = incoming.category_name
+                .as_deref()
+                .and_then(|name| local.categories.get_category_id_by_name(name));
+            incoming.favorite |= local_favorite;
+            // Generator profiles aren't merged, so only links to profiles this vault has are kept
+            incoming.generator_profile_id = incoming.generator_profile_id
+                .filter(|profile_id| local.generator_profiles.contains(profile_id))
+                .or(local_profile);
+
+            let attachment_count = incoming.attachments.len();
+            incoming.attachments.retain(|attachment| !missing_attachments.contains(&attachment.id));
+            self.report.missing_attachments += attachment_count - incoming.attachments.len();
+
+            if local.entries.contains_key(id) {
+                local.delete_entry(id)?;
+            }
+            local.insert_entry(incoming)?;
+        }
+
+        self.report.dry_run = false;
+        Ok(self.report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::kind::EntryKind;
+    use crate::entry::overview::EntryData;
+
+    fn entry_data(title: &str, password: &str) -> EntryData {
+        EntryData {
+            title: title.to_string(),
+            username: None,
+            password: Some(password.to_string()),
+            url: None,
+            notes: None,
+            category_name: None,
+            favorite: false,
+            totp: None,
+            custom_fields: Vec::new(),
+            kind: EntryKind::default(),
+            details: None,
+        }
+    }
+
+    // Two copies of a vault holding the same single entry
+    fn copies() -> (KeyHierarchy, EntryCollection, EntryCollection, Uuid) {
+        let (key_hierarchy, _) = KeyHierarchy::new(b"master password").unwrap();
+        let mut local = EntryCollection::new();
+        let id = local.create_entry(entry_data("Bank", "first password"), &key_hierarchy).unwrap();
+        let other = serde_cbor::from_slice(&serde_cbor::to_vec(&local).unwrap()).unwrap();
+        (key_hierarchy, local, other, id)
+    }
+
+    #[test]
+    fn both_sides_changing_the_title_is_a_conflict() {
+        let (key_hierarchy, mut local, mut other, id) = copies();
+        local.update_entry(&id, entry_data("Bank (personal)", "first password"), &key_hierarchy).unwrap();
+        other.update_entry(&id, entry_data("Bank (joint)", "first password"), &key_hierarchy).unwrap();
+
+        assert_eq!(compare(&local.entries[&id], &other.entries[&id]), Outcome::Conflict);
+    }
+
+    #[test]
+    fn a_newer_copy_with_more_history_wins() {
+        let (key_hierarchy, local, mut other, id) = copies();
+        other.update_entry(&id, entry_data("Bank", "second password"), &key_hierarchy).unwrap();
+
+        assert_eq!(compare(&local.entries[&id], &other.entries[&id]), Outcome::Update);
+        assert_eq!(compare(&other.entries[&id], &local.entries[&id]), Outcome::KeepLocal);
+    }
+}
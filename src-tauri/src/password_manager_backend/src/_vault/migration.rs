@@ -0,0 +1,135 @@
+// password_manager_backend/src/_vault/migration.rs
+
+use std::collections::BTreeMap;
+use serde_cbor::Value;
+use uuid::Uuid;
+use crate::_vault::CURRENT_FORMAT_VERSION;
+use crate::error::app_error::AppError;
+
+/// A single upgrade step that turns a payload of `from_version` into `from_version + 1`.
+///
+/// Migrations work on the raw CBOR value tree rather than on `VaultManager` so that
+/// old layouts never need a matching Rust type to be kept around.
+pub struct Migration {
+    pub from_version: u16,
+    pub description: &'static str,
+    pub migrate: fn(Value) -> Result<Value, String>,
+}
+
+/// Registry of every format upgrade, ordered by `from_version`.
+/// When the vault layout changes: bump `CURRENT_FORMAT_VERSION` and append a step here.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from_version: 0,
+        description: "Headerless CBOR vault to versioned header",
+        migrate: migrate_v0_to_v1,
+    },
+    Migration {
+        from_version: 1,
+        description: "Plaintext entry collection to sealed entry collection",
+        migrate: migrate_v1_to_v2,
+    },
+    Migration {
+        from_version: 2,
+        description: "Password hash to split verifier (legacy key scheme)",
+        migrate: migrate_v2_to_v3,
+    },
+    Migration {
+        from_version: 3,
+        description: "Vault id for associated data binding",
+        migrate: migrate_v3_to_v4,
+    },
+    Migration {
+        from_version: 4,
+        description: "Whole-file MAC trailer",
+        migrate: migrate_v4_to_v5,
+    },
+];
+
+/// Runs every registered migration between `version` and `CURRENT_FORMAT_VERSION`, one step at a time
+pub fn migrate_to_current(mut version: u16, mut value: Value) -> Result<Value, AppError> {
+    while version < CURRENT_FORMAT_VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.from_version == version)
+            .ok_or_else(|| AppError::MigrationFailed(version, "No migration registered".to_string()))?;
+
+        println!("Migrating vault format v{} -> v{}: {}", version, version + 1, migration.description);
+        value = (migration.migrate)(value)
+            .map_err(|reason| AppError::MigrationFailed(version, reason))?;
+        version += 1;
+    }
+
+    Ok(value)
+}
+
+// v0 -> v1: the payload itself is unchanged, only the file header is new
+fn migrate_v0_to_v1(value: Value) -> Result<Value, String> {
+    match value {
+        Value::Map(_) => Ok(value),
+        _ => Err("Vault payload is not a map".to_string()),
+    }
+}
+
+
+// v1 -> v2: the entry collection can only be encrypted once the MEK is available,
+// so park it under `legacy_entry_collection` and let the first save after login seal it
+fn migrate_v1_to_v2(mut value: Value) -> Result<Value, String> {
+    let map = as_map_mut(&mut value)?;
+
+    let collection = map
+        .remove(&text_key("entry_collection"))
+        .ok_or_else(|| "Missing entry_collection".to_string())?;
+    map.insert(text_key("legacy_entry_collection"), collection);
+
+    Ok(value)
+}
+
+// v2 -> v3: older vaults stored the raw Argon2 output, which is also the MEK-wrapping key.
+// It is kept as the verifier and flagged as `Legacy`; `AuthService::unlock` replaces both on next login.
+fn migrate_v2_to_v3(mut value: Value) -> Result<Value, String> {
+    let auth_service = as_map_mut(&mut value)?
+        .get_mut(&text_key("auth_service"))
+        .ok_or_else(|| "Missing auth_service".to_string())?;
+    let auth_service = as_map_mut(auth_service)?;
+
+    let hash = auth_service
+        .remove(&text_key("master_password_hash"))
+        .ok_or_else(|| "Missing master_password_hash".to_string())?;
+    auth_service.insert(text_key("verifier"), hash);
+    auth_service.insert(text_key("key_scheme"), text_key("Legacy"));
+
+    Ok(value)
+}
+
+// v3 -> v4: ciphertexts are now bound to their owner through AES-GCM associated data, which needs a
+// stable vault id. Existing blobs stay readable without AAD and are re-encrypted on the next login.
+fn migrate_v3_to_v4(mut value: Value) -> Result<Value, String> {
+    let auth_service = as_map_mut(&mut value)?
+        .get_mut(&text_key("auth_service"))
+        .ok_or_else(|| "Missing auth_service".to_string())?;
+    let auth_service = as_map_mut(auth_service)?;
+
+    auth_service.insert(text_key("vault_id"), Value::Bytes(Uuid::new_v4().as_bytes().to_vec()));
+    auth_service.insert(text_key("mek_wrap_version"), Value::Null);
+
+    Ok(value)
+}
+
+// v4 -> v5: only the file gained a MAC trailer; it can only be computed with the MEK,
+// so older files are accepted unverified once and signed by the save after login
+fn migrate_v4_to_v5(value: Value) -> Result<Value, String> {
+    Ok(value)
+}
+
+// Helpers for migrations that edit the value tree
+fn as_map_mut(value: &mut Value) -> Result<&mut BTreeMap<Value, Value>, String> {
+    match value {
+        Value::Map(map) => Ok(map),
+        _ => Err("Vault payload is not a map".to_string()),
+    }
+}
+
+fn text_key(key: &str) -> Value {
+    Value::Text(key.to_string())
+}
@@ -1,6 +1,14 @@
 pub mod password_generation;
 pub mod vault_manager;
 pub mod vault_storage;
+pub mod vault_format;
+pub mod migration;
+pub mod auto_lock;
+pub mod portable;
+pub mod backup;
+pub mod merge;
+pub mod wordlist;
+pub mod password_rules;
 
 // Password Generation Constants
 pub const ABSOLUTE_MIN_LENGTH: usize = 5;
@@ -9,5 +17,44 @@ pub const DEFAULT_LENGTH: usize = 16;
 pub const DEFAULT_MIN_NUMBERS: usize = 2;
 pub const DEFAULT_MIN_SYMBOLS: usize = 2;
 
+// Passphrase Constants
+pub const MIN_PASSPHRASE_WORDS: usize = 3;
+pub const MAX_PASSPHRASE_WORDS: usize = 20;
+pub const DEFAULT_PASSPHRASE_WORDS: usize = 6;
+pub const DEFAULT_PASSPHRASE_SEPARATOR: &str = "-";
+pub const MAX_PASSPHRASE_SEPARATOR_LENGTH: usize = 3;
+
+// Password Rules Constants
+pub const MAX_GENERATOR_PROFILES: usize = 500;
+pub const MAX_PROFILE_NAME_LENGTH: usize = 64;
+pub const MAX_REQUIRED_SETS: usize = 10;
+pub const MAX_RULE_ATTEMPTS: usize = 10_000; // Candidates tried before rules are reported as unsatisfiable
+
+// Auto-Lock Constants
+pub const DEFAULT_IDLE_TIMEOUT_MINUTES: u32 = 15;
+pub const MAX_IDLE_TIMEOUT_MINUTES: u32 = 24 * 60;
+
+// Vault File Format Constants
+pub const VAULT_MAGIC: [u8; 8] = *b"PAWPASS\0";
+pub const LEGACY_FORMAT_VERSION: u16 = 0; // Headerless CBOR written before the header existed
+pub const CURRENT_FORMAT_VERSION: u16 = 5;
+pub const FIRST_MAC_FORMAT_VERSION: u16 = 5; // Files from this version on end with a whole-file MAC
+pub const FILE_MAC_LENGTH: usize = 32; // HMAC-SHA256
+
+// Backup Retention Constants
+pub const DEFAULT_KEEP_LAST_BACKUPS: u32 = 5;
+pub const DEFAULT_KEEP_DAILY_BACKUPS: u32 = 7;
+pub const DEFAULT_KEEP_WEEKLY_BACKUPS: u32 = 4;
+pub const DEFAULT_KEEP_MONTHLY_BACKUPS: u32 = 6;
+pub const MAX_BACKUP_RETENTION: u32 = 1000; // Per tier
+pub const BACKUP_FILE_PREFIX: &str = "vault_backup_";
+pub const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S";
+pub const DEFAULT_AUTO_BACKUP_INTERVAL_HOURS: u32 = 24;
+pub const MAX_AUTO_BACKUP_INTERVAL_HOURS: u32 = 30 * 24;
+
+// Portable Export Constants
+pub const PORTABLE_MAGIC: [u8; 8] = *b"PAWPORT\0";
+pub const PORTABLE_FORMAT_VERSION: u16 = 1;
+
 
 
@@ -1,7 +1,12 @@
 use std::collections::HashSet;
 use rand::SeedableRng; // Used for ChaCha20Rng, brings over from_entropy()
 use serde::{Deserialize, Serialize};
-use crate::_vault::{ABSOLUTE_MAX_LENGTH, ABSOLUTE_MIN_LENGTH, DEFAULT_LENGTH};
+use crate::_vault::{
+    ABSOLUTE_MAX_LENGTH, ABSOLUTE_MIN_LENGTH, DEFAULT_LENGTH, DEFAULT_PASSPHRASE_SEPARATOR, DEFAULT_PASSPHRASE_WORDS,
+    MAX_PASSPHRASE_SEPARATOR_LENGTH, MAX_PASSPHRASE_WORDS, MAX_RULE_ATTEMPTS, MIN_PASSPHRASE_WORDS,
+};
+use crate::_vault::password_rules::PasswordRules;
+use crate::_vault::wordlist;
 use crate::error::password_generation_error::PasswordGenerationError;
 use rand_chacha::ChaCha20Rng;
 
@@ -29,6 +34,58 @@ pub struct PasswordGenerator {
 
     // Pre-computed Character Sets (computed once at initialization)
     pub(crate) available_chars: CharacterSets,
+
+    // Passphrase Controls (vaults from before passphrases generate characters)
+    #[serde(default)]
+    mode: GenerationMode,
+    #[serde(default)]
+    passphrase: PassphraseSettings,
+}
+
+/// Whether `generate` builds a random character string or a passphrase of random words
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GenerationMode {
+    #[default]
+    Characters,
+    Passphrase,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Capitalization {
+    #[default]
+    Lowercase,
+    // First letter of every word
+    Capitalized,
+    Uppercase,
+    // Each word is capitalized or not at random, adding a bit per word
+    Random,
+}
+
+/// Settings for passphrase mode. Words are drawn from the embedded 7776-word list, so each one
+/// adds about 12.9 bits; the default of 6 words gives roughly 77.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PassphraseSettings {
+    pub word_count: usize,
+    pub separator: String,
+    pub capitalization: Capitalization,
+    // Added after the last word, for sites that insist on a digit or symbol
+    pub append_number: bool,
+    pub append_symbol: bool,
+}
+
+impl Default for PassphraseSettings {
+    fn default() -> Self {
+        Self {
+            word_count: DEFAULT_PASSPHRASE_WORDS,
+            separator: DEFAULT_PASSPHRASE_SEPARATOR.to_string(),
+            capitalization: Capitalization::default(),
+            append_number: false,
+            append_symbol: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +105,10 @@ pub struct PasswordGeneratorSettings {
     pub min_symbols: usize,
     #[serde(rename = "excludeAmbiguous")]
     pub exclude_ambiguous: bool,
+    #[serde(default)]
+    pub mode: GenerationMode,
+    #[serde(default)]
+    pub passphrase: PassphraseSettings,
 }
 
 /// Separate struct to manage the various character sets
@@ -92,9 +153,11 @@ impl PasswordGenerator {
         self.min_numbers = settings.min_numbers;
         self.min_symbols = settings.min_symbols;
         self.exclude_ambiguous = settings.exclude_ambiguous;
+        self.mode = settings.mode;
 
         // Calculate minimum required length based on chosen options
         self.min_required = self.calculate_min_required(&settings);
+        self.passphrase = settings.passphrase;
 
         Ok(())
     }
@@ -109,6 +172,8 @@ impl PasswordGenerator {
             min_numbers: self.min_numbers,
             min_symbols: self.min_symbols,
             exclude_ambiguous: self.exclude_ambiguous,
+            mode: self.mode,
+            passphrase: self.passphrase.clone(),
         }
     }
 
@@ -133,13 +198,33 @@ impl PasswordGenerator {
             return Err(PasswordGenerationError::ExcessiveMinimums(required_length, settings.length));
         }
 
+        // Passphrase settings are kept while generating characters too, so they're always checked
+        let passphrase = &settings.passphrase;
+        if !(MIN_PASSPHRASE_WORDS..=MAX_PASSPHRASE_WORDS).contains(&passphrase.word_count) {
+            return Err(PasswordGenerationError::WordCountOutOfRange(passphrase.word_count));
+        }
+        // Letters in the separator would blur where words end
+        if passphrase.separator.chars().count() > MAX_PASSPHRASE_SEPARATOR_LENGTH
+            || passphrase.separator.chars().any(|c| c.is_alphabetic() || c.is_control())
+        {
+            return Err(PasswordGenerationError::InvalidSeparator(passphrase.separator.clone()));
+        }
+
         Ok(())
     }
 }
 
 impl PasswordGenerator {
-    /// Generates a password meeting all configured requirements
+    /// Generates a password or passphrase, depending on the configured mode
     pub fn generate(&self) -> Result<String, PasswordGenerationError> {
+        match self.mode {
+            GenerationMode::Characters => self.generate_characters(),
+            GenerationMode::Passphrase => self.generate_passphrase(),
+        }
+    }
+
+    /// Generates a password meeting all configured requirements
+    fn generate_characters(&self) -> Result<String, PasswordGenerationError> {
         // Initialize our secure RNG
         let mut rng = ChaCha20Rng::from_entropy();
 
@@ -184,6 +269,131 @@ impl PasswordGenerator {
         Ok(password_chars.into_iter().collect())
     }
 
+    /// Generates a passphrase of independently chosen words. Words may repeat, since ruling
+    /// repeats out would make each pick depend on the ones before it.
+    fn generate_passphrase(&self) -> Result<String, PasswordGenerationError> {
+        use rand::Rng;
+        use rand::seq::SliceRandom;
+
+        let mut rng = ChaCha20Rng::from_entropy();
+        let settings = &self.passphrase;
+
+        let mut words = Vec::with_capacity(settings.word_count);
+        for _ in 0..settings.word_count {
+            let word = wordlist::WORDS.choose(&mut rng).ok_or(PasswordGenerationError::NoValidCharacters)?;
+            let word = match settings.capitalization {
+                Capitalization::Lowercase => word.to_string(),
+                Capitalization::Capitalized => capitalize(word),
+                Capitalization::Uppercase => word.to_uppercase(),
+                Capitalization::Random if rng.gen_bool(0.5) => capitalize(word),
+                Capitalization::Random => word.to_string(),
+            };
+            words.push(word);
+        }
+        let mut passphrase = words.join(&settings.separator);
+
+        if settings.append_number {
+            let number = self.available_chars.numbers.choose(&mut rng).ok_or(PasswordGenerationError::EmptyCharacterSet)?;
+            passphrase.push(*number);
+        }
+        if settings.append_symbol {
+            let symbol = self.available_chars.symbols.choose(&mut rng).ok_or(PasswordGenerationError::EmptyCharacterSet)?;
+            passphrase.push(*symbol);
+        }
+
+        Ok(passphrase)
+    }
+
+    /// Generates a character password that also meets a site's rules. Passphrase mode is ignored,
+    /// since sites with rules tend to reject long passwords or ones made of words.
+    ///
+    /// The configured length is clamped to the rules' bounds. Limits that can't be built in up
+    /// front, like runs of the same character, are met by drawing candidates until one passes.
+    pub fn generate_with_rules(&self, rules: &PasswordRules) -> Result<String, PasswordGenerationError> {
+        use rand::seq::SliceRandom;
+
+        rules.validate()?;
+        let mut rng = ChaCha20Rng::from_entropy();
+
+        let (mut pool, mut required) = match &rules.allowed {
+            Some(allowed) => (rules.filter(allowed.chars()), Vec::new()),
+            None => self.rule_character_sets(rules),
+        };
+        // A required character is allowed even if the site forgot to list it
+        for set in &rules.required {
+            let set = rules.filter(set.chars());
+            pool.extend(&set);
+            required.push(set);
+        }
+        pool.sort_unstable();
+        pool.dedup();
+        if pool.is_empty() {
+            return Err(PasswordGenerationError::NoValidCharacters);
+        }
+
+        let length = rules.max_length.map_or(self.length, |max| self.length.min(max));
+        let length = rules.min_length.map_or(length, |min| length.max(min));
+        if required.len() > length {
+            return Err(PasswordGenerationError::ExcessiveMinimums(required.len(), length));
+        }
+
+        for _ in 0..MAX_RULE_ATTEMPTS {
+            let mut password_chars = Vec::with_capacity(length);
+            for set in &required {
+                password_chars.push(*set.choose(&mut rng).ok_or(PasswordGenerationError::NoValidCharacters)?);
+            }
+            while password_chars.len() < length {
+                password_chars.push(*pool.choose(&mut rng).ok_or(PasswordGenerationError::NoValidCharacters)?);
+            }
+            self.shuffle_password(&mut password_chars, &mut rng);
+
+            if rules.accepts(&password_chars) {
+                return Ok(password_chars.into_iter().collect());
+            }
+        }
+
+        Err(PasswordGenerationError::RulesUnsatisfiable(format!(
+            "no candidate passed after {} attempts", MAX_RULE_ATTEMPTS
+        )))
+    }
+
+    /// The enabled character sets, filtered by the rules, and one set per required character.
+    /// Ambiguous characters are dropped outright rather than one per group, since candidates
+    /// are drawn independently.
+    fn rule_character_sets(&self, rules: &PasswordRules) -> (Vec<char>, Vec<Vec<char>>) {
+        let symbols: Vec<char> = match &rules.symbols {
+            Some(symbols) => symbols.chars().collect(),
+            None => self.available_chars.symbols.clone(),
+        };
+        let filter = |set: &[char]| {
+            rules.filter(set.iter().copied().filter(|c| {
+                !self.exclude_ambiguous || !self.available_chars.ambiguous.iter().any(|group| group.contains(c))
+            }))
+        };
+        // The site's cap on symbols wins over the generator's minimum
+        let min_symbols = rules.max_symbols.map_or(self.min_symbols, |max| self.min_symbols.min(max));
+
+        let mut pool = Vec::new();
+        let mut required = Vec::new();
+        let sets = [
+            (self.use_lowercase, &self.available_chars.lowercase, 1),
+            (self.use_uppercase, &self.available_chars.uppercase, 1),
+            (self.use_numbers, &self.available_chars.numbers, self.min_numbers),
+            (self.use_symbols, &symbols, min_symbols),
+        ];
+        for (enabled, set, minimum) in sets {
+            let set = filter(set);
+            // A set the rules leave empty is skipped along with its minimum
+            if !enabled || set.is_empty() {
+                continue;
+            }
+            pool.extend(&set);
+            required.extend(std::iter::repeat_n(set, minimum));
+        }
+
+        (pool, required)
+    }
+
     /// Adds a random character from a specific character set while respecting ambiguous rules
     fn add_random_char_from_set(
         &self,
@@ -267,10 +477,34 @@ impl PasswordGenerator {
     }
 
     pub fn calculate_entropy(&self) -> f64 {
+        if self.mode == GenerationMode::Passphrase {
+            return self.calculate_passphrase_entropy();
+        }
+
         let charset_size = self.get_effective_charset_size();
         (self.length as f64) * (charset_size).log2()
     }
 
+    // Every word is a uniform pick from the list and the separator is assumed known to an attacker.
+    // Random capitalization doubles the choices per word, and appended characters are uniform picks.
+    fn calculate_passphrase_entropy(&self) -> f64 {
+        let settings = &self.passphrase;
+
+        let mut bits_per_word = (wordlist::WORDS.len() as f64).log2();
+        if settings.capitalization == Capitalization::Random {
+            bits_per_word += 1.0;
+        }
+
+        let mut entropy = settings.word_count as f64 * bits_per_word;
+        if settings.append_number {
+            entropy += (self.available_chars.numbers.len() as f64).log2();
+        }
+        if settings.append_symbol {
+            entropy += (self.available_chars.symbols.len() as f64).log2();
+        }
+        entropy
+    }
+
     fn get_effective_charset_size(&self) -> f64 {
         let mut size = 0.0;
         if self.use_lowercase { size += self.available_chars.lowercase.len() as f64; }
@@ -287,6 +521,14 @@ impl PasswordGenerator {
 
 }
 
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 impl Default for PasswordGenerator {
     fn default() -> Self {
         // Initialize with secure defaults:
@@ -305,6 +547,8 @@ impl Default for PasswordGenerator {
             min_symbols: 2,
             exclude_ambiguous: true,
             available_chars: CharacterSets::default(),
+            mode: GenerationMode::default(),
+            passphrase: PassphraseSettings::default(),
         };
 
         password_generator
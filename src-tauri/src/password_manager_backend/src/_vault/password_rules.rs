@@ -0,0 +1,285 @@
+// password_manager_backend/src/_vault/password_rules.rs
+
+use std::collections::{BTreeSet, HashMap};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::_vault::{
+    ABSOLUTE_MAX_LENGTH, ABSOLUTE_MIN_LENGTH, MAX_GENERATOR_PROFILES, MAX_PROFILE_NAME_LENGTH, MAX_REQUIRED_SETS,
+};
+use crate::error::password_generation_error::PasswordGenerationError;
+
+/// A site's constraints on passwords, applied on top of the generator settings.
+///
+/// When `allowed` is unset the generator's own character sets and minimums still apply, with
+/// `symbols` swapped in for the default symbols. Rules parsed from Apple's `passwordrules` always
+/// set `allowed`, since that syntax lists every permitted character.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PasswordRules {
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    // Every character the site accepts
+    pub allowed: Option<String>,
+    // Symbols the site accepts, when `allowed` is unset
+    pub symbols: Option<String>,
+    // The password contains at least one character from each set
+    pub required: Vec<String>,
+    // Never used, even if another setting allows them
+    pub forbidden: String,
+    // Longest run of the same character
+    pub max_consecutive: Option<usize>,
+    pub max_symbols: Option<usize>,
+}
+
+/// Named rules saved in the vault, e.g. for a bank that caps passwords at 16 characters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratorProfile {
+    pub id: Uuid,
+    pub name: String,
+    pub rules: PasswordRules,
+}
+
+// Apple's `special` class, minus the space: a generated space is too easy to lose when copying
+const SPECIAL_CHARS: &str = "-~!@#$%^&*_+=`|(){}[:;\"'<>,.?]/\\";
+
+fn is_symbol(c: char) -> bool {
+    !c.is_ascii_alphanumeric()
+}
+
+fn invalid(message: impl Into<String>) -> PasswordGenerationError {
+    PasswordGenerationError::InvalidRules(message.into())
+}
+
+fn char_class(name: &str) -> Option<String> {
+    let chars = match name {
+        "upper" => ('A'..='Z').collect(),
+        "lower" => ('a'..='z').collect(),
+        "digit" => ('0'..='9').collect(),
+        "special" => SPECIAL_CHARS.to_string(),
+        // Only ASCII is ever generated, so `unicode` is treated like `ascii-printable`
+        "ascii-printable" | "unicode" => ('!'..='~').collect(),
+        _ => return None,
+    };
+    Some(chars)
+}
+
+// Parses a comma-separated list of classes and `[...]` custom sets into one set of characters
+fn parse_classes(value: &str) -> Result<String, PasswordGenerationError> {
+    let mut chars = String::new();
+    let mut rest = value.trim();
+
+    while !rest.is_empty() {
+        if let Some(custom) = rest.strip_prefix('[') {
+            // `]` may itself be a member as long as it comes last, so the set ends at the
+            // last `]` before the next comma
+            let end = custom.find("],").map_or_else(|| custom.rfind(']'), Some)
+                .ok_or_else(|| invalid("Unclosed custom character set"))?;
+            chars.extend(custom[..end].chars().filter(|c| c.is_ascii_graphic()));
+            rest = &custom[end + 1..];
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let name = rest[..end].trim().to_ascii_lowercase();
+            chars.push_str(&char_class(&name).ok_or_else(|| invalid(format!("Unknown character class: {}", name)))?);
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start().strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+
+    Ok(chars)
+}
+
+fn parse_number(name: &str, value: &str) -> Result<usize, PasswordGenerationError> {
+    value.trim().parse().map_err(|_| invalid(format!("{} needs a number", name)))
+}
+
+// Sorted and without duplicates, so equal sets compare equal
+fn normalize(chars: impl Iterator<Item = char>) -> String {
+    chars.collect::<BTreeSet<_>>().into_iter().collect()
+}
+
+impl PasswordRules {
+    /// Parses Apple's `passwordrules` syntax, e.g.
+    /// `minlength: 8; maxlength: 16; required: lower; required: upper; required: digit; required: [-().&@?'#,/"+]; max-consecutive: 2;`
+    ///
+    /// Unknown properties are ignored, as the syntax requires. A rule without any `required` or
+    /// `allowed` properties permits every printable ASCII character.
+    pub fn parse_apple(rules: &str) -> Result<Self, PasswordGenerationError> {
+        let mut parsed = Self::default();
+        let mut allowed = String::new();
+
+        for rule in rules.split(';').map(str::trim).filter(|rule| !rule.is_empty()) {
+            let (name, value) = rule.split_once(':').ok_or_else(|| invalid(format!("Expected name: value in {:?}", rule)))?;
+            let name = name.trim().to_ascii_lowercase();
+            match name.as_str() {
+                "required" => {
+                    let set = parse_classes(value)?;
+                    allowed.push_str(&set);
+                    parsed.required.push(normalize(set.chars()));
+                }
+                "allowed" => allowed.push_str(&parse_classes(value)?),
+                "minlength" => {
+                    let length = parse_number("minlength", value)?;
+                    parsed.min_length = Some(parsed.min_length.map_or(length, |current| current.max(length)));
+                }
+                "maxlength" => {
+                    let length = parse_number("maxlength", value)?;
+                    parsed.max_length = Some(parsed.max_length.map_or(length, |current| current.min(length)));
+                }
+                "max-consecutive" => {
+                    let run = parse_number("max-consecutive", value)?;
+                    parsed.max_consecutive = Some(parsed.max_consecutive.map_or(run, |current| current.min(run)));
+                }
+                _ => {}
+            }
+        }
+
+        if allowed.is_empty() {
+            allowed = char_class("ascii-printable").unwrap_or_default();
+        }
+        parsed.allowed = Some(normalize(allowed.chars()));
+        // Site limits wider than the vault's are narrowed; narrower ones the generator can't meet fail validation
+        parsed.min_length = parsed.min_length.map(|length| length.max(ABSOLUTE_MIN_LENGTH));
+        parsed.max_length = parsed.max_length.map(|length| length.min(ABSOLUTE_MAX_LENGTH));
+
+        parsed.validate()?;
+        Ok(parsed)
+    }
+
+    pub fn validate(&self) -> Result<(), PasswordGenerationError> {
+        if let Some(min) = self.min_length {
+            if min < ABSOLUTE_MIN_LENGTH {
+                return Err(PasswordGenerationError::LengthTooShort(min));
+            }
+            if min > ABSOLUTE_MAX_LENGTH {
+                return Err(PasswordGenerationError::LengthTooLong(min));
+            }
+        }
+        if let Some(max) = self.max_length {
+            if max < ABSOLUTE_MIN_LENGTH {
+                return Err(PasswordGenerationError::LengthTooShort(max));
+            }
+            if max > ABSOLUTE_MAX_LENGTH {
+                return Err(PasswordGenerationError::LengthTooLong(max));
+            }
+            if max < self.min_length.unwrap_or(ABSOLUTE_MIN_LENGTH) {
+                return Err(invalid("Maximum length is below the minimum length"));
+            }
+        }
+        if self.max_consecutive == Some(0) {
+            return Err(invalid("Max consecutive characters must be at least 1"));
+        }
+        if self.required.len() > MAX_REQUIRED_SETS {
+            return Err(invalid(format!("At most {} required character sets are supported", MAX_REQUIRED_SETS)));
+        }
+
+        let sets = self.allowed.iter().chain(&self.symbols).chain(&self.required).chain(std::iter::once(&self.forbidden));
+        if sets.flat_map(|set| set.chars()).any(|c| !c.is_ascii_graphic()) {
+            return Err(invalid("Only printable ASCII characters other than space can be used"));
+        }
+        if self.required.iter().any(|set| self.filter(set.chars()).is_empty()) {
+            return Err(invalid("A required character set has nothing left once forbidden characters are removed"));
+        }
+
+        Ok(())
+    }
+
+    /// Whether a candidate password stays within the limits that can't be built in up front
+    pub(crate) fn accepts(&self, password: &[char]) -> bool {
+        let symbols = password.iter().filter(|c| is_symbol(**c)).count();
+        let longest_run = password
+            .chunk_by(|a, b| a == b)
+            .map(<[char]>::len)
+            .max()
+            .unwrap_or(0);
+
+        self.max_symbols.is_none_or(|max| symbols <= max)
+            && self.max_consecutive.is_none_or(|max| longest_run <= max)
+    }
+
+    /// Removes forbidden characters, and symbols when none are allowed
+    pub(crate) fn filter(&self, chars: impl IntoIterator<Item = char>) -> Vec<char> {
+        chars
+            .into_iter()
+            .filter(|c| !self.forbidden.contains(*c))
+            .filter(|c| self.max_symbols != Some(0) || !is_symbol(*c))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// The user's saved profiles. They are kept with the entries, inside the sealed part of the
+/// vault, since their names tend to reveal which sites have accounts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GeneratorProfiles {
+    profiles: HashMap<Uuid, GeneratorProfile>,
+}
+
+impl GeneratorProfiles {
+    /// Creates a profile, or replaces the one with the given id. Names must be unique.
+    pub fn save(&mut self, id: Option<Uuid>, name: String, rules: PasswordRules) -> Result<Uuid, PasswordGenerationError> {
+        let name = name.trim().to_string();
+        if name.is_empty() || name.len() > MAX_PROFILE_NAME_LENGTH {
+            return Err(invalid(format!("Profile names must be 1 to {} characters", MAX_PROFILE_NAME_LENGTH)));
+        }
+        rules.validate()?;
+
+        if let Some(id) = id {
+            if !self.profiles.contains_key(&id) {
+                return Err(PasswordGenerationError::ProfileNotFound(id));
+            }
+        } else if self.profiles.len() >= MAX_GENERATOR_PROFILES {
+            return Err(invalid(format!("At most {} profiles can be saved", MAX_GENERATOR_PROFILES)));
+        }
+        let duplicate = self.profiles
+            .values()
+            .any(|profile| Some(profile.id) != id && profile.name.eq_ignore_ascii_case(&name));
+        if duplicate {
+            return Err(invalid(format!("A profile named {} already exists", name)));
+        }
+
+        let id = id.unwrap_or_else(Uuid::new_v4);
+        self.profiles.insert(id, GeneratorProfile { id, name, rules });
+        Ok(id)
+    }
+
+    pub fn remove(&mut self, id: &Uuid) -> Result<GeneratorProfile, PasswordGenerationError> {
+        self.profiles.remove(id).ok_or(PasswordGenerationError::ProfileNotFound(*id))
+    }
+
+    pub fn get(&self, id: &Uuid) -> Result<&GeneratorProfile, PasswordGenerationError> {
+        self.profiles.get(id).ok_or(PasswordGenerationError::ProfileNotFound(*id))
+    }
+
+    pub fn contains(&self, id: &Uuid) -> bool {
+        self.profiles.contains_key(id)
+    }
+
+    /// Every profile, sorted by name
+    pub fn list(&self) -> Vec<GeneratorProfile> {
+        let mut profiles: Vec<_> = self.profiles.values().cloned().collect();
+        profiles.sort_by_key(|profile| profile.name.to_lowercase());
+        profiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_a_site_maxlength_below_the_generator_minimum() {
+        assert!(matches!(
+            PasswordRules::parse_apple("maxlength: 4;"),
+            Err(PasswordGenerationError::LengthTooShort(4))
+        ));
+    }
+
+    #[test]
+    fn narrows_site_lengths_wider_than_the_generator_limits() {
+        let rules = PasswordRules::parse_apple("minlength: 4; maxlength: 1000;").unwrap();
+        assert_eq!(rules.min_length, Some(ABSOLUTE_MIN_LENGTH));
+        assert_eq!(rules.max_length, Some(ABSOLUTE_MAX_LENGTH));
+    }
+}
@@ -0,0 +1,138 @@
+// password_manager_backend/src/_vault/portable.rs
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+use crate::_vault::{PORTABLE_FORMAT_VERSION, PORTABLE_MAGIC};
+use crate::_vault::password_generation::PasswordGeneratorSettings;
+use crate::_vault::vault_format::HeaderReader;
+use crate::_vault::vault_manager::VaultManager;
+use crate::crypto::{EncryptedData, KeyDerivation, KeyHierarchy};
+use crate::entry::overview::EntryData;
+use crate::error::app_error::AppError;
+use crate::error::import_error::ImportError;
+
+/// Whether an imported archive is added to the current entries or takes their place
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PortableImportMode {
+    Merge,
+    Replace,
+}
+
+/// Decrypted contents of a portable export.
+///
+/// Unlike a backup, the archive doesn't depend on the vault's master key or `AuthService`:
+/// it is sealed under a password chosen for the export, with its own Argon2id salt.
+///
+/// On-disk layout (all integers little-endian):
+/// ```markdown
+/// | magic "PAWPORT\0"   | 8 bytes           |
+/// | format version      | u16               |
+/// | memory cost         | u32               |
+/// | time cost           | u32               |
+/// | parallelism         | u32               |
+/// | salt length         | u8                |
+/// | salt                | salt length bytes |
+/// | sealed payload      | CBOR `EncryptedData` of the CBOR `PortableVault` |
+/// ```
+/// The header is the AAD of the payload, so it can't be altered without failing decryption.
+#[derive(Serialize, Deserialize)]
+pub struct PortableVault {
+    #[serde(rename = "exportedAt")]
+    pub exported_at: DateTime<Utc>,
+    // Every category, including ones no entry uses
+    pub categories: Vec<String>,
+    pub entries: Vec<EntryData>,
+    #[serde(rename = "passwordGeneratorSettings")]
+    pub password_generator_settings: PasswordGeneratorSettings,
+}
+
+impl PortableVault {
+    /// Decrypts the unlocked vault into an archive; attachments and history are not included
+    pub fn from_vault(vault_manager: &VaultManager, key_hierarchy: &KeyHierarchy) -> Result<Self, AppError> {
+        let collection = &vault_manager.entry_collection;
+
+        let mut entries = Vec::with_capacity(collection.entries.len());
+        for id in collection.entries.keys() {
+            entries.push(collection.get_decrypted_entry(id, key_hierarchy)?.to_entry_data());
+        }
+        entries.sort_by_key(|entry| entry.title.to_lowercase());
+
+        let mut categories: Vec<String> = collection.categories
+            .get_all_categories()
+            .iter()
+            .map(|category| category.name().to_string())
+            .collect();
+        categories.sort_by_key(|name| name.to_lowercase());
+
+        Ok(Self {
+            exported_at: Utc::now(),
+            categories,
+            entries,
+            password_generator_settings: vault_manager.password_generator_settings.get_settings(),
+        })
+    }
+
+    /// Encrypts the archive under `password` and returns the file contents
+    pub fn seal(&self, password: &str) -> Result<Vec<u8>, AppError> {
+        let key_derivation = KeyDerivation::default();
+        let salt = key_derivation.generate_salt()?;
+        let key = key_derivation.derive_key(password.as_bytes(), &salt)?;
+
+        let mut bytes = Vec::with_capacity(31 + salt.len());
+        bytes.extend_from_slice(&PORTABLE_MAGIC);
+        bytes.extend_from_slice(&PORTABLE_FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&key_derivation.memory_cost().to_le_bytes());
+        bytes.extend_from_slice(&key_derivation.time_cost().to_le_bytes());
+        bytes.extend_from_slice(&key_derivation.parallelism().to_le_bytes());
+        bytes.push(salt.len() as u8);
+        bytes.extend_from_slice(&salt);
+
+        let mut payload = serde_cbor::to_vec(self)
+            .map_err(|e| AppError::SerializationFailed(e.to_string()))?;
+        let sealed = KeyHierarchy::encrypt_with_key(key.as_ref(), &payload, &bytes);
+        payload.zeroize();
+
+        let sealed = serde_cbor::to_vec(&sealed?)
+            .map_err(|e| AppError::SerializationFailed(e.to_string()))?;
+        bytes.extend_from_slice(&sealed);
+        Ok(bytes)
+    }
+
+    /// Decrypts an archive written by `seal`
+    pub fn open(bytes: &[u8], password: &str) -> Result<Self, AppError> {
+        if !bytes.starts_with(&PORTABLE_MAGIC) {
+            return Err(ImportError::UnrecognizedFormat.into());
+        }
+
+        let truncated = |_| ImportError::Malformed("export is truncated".to_string());
+        let mut reader = HeaderReader { bytes, position: PORTABLE_MAGIC.len() };
+        let format_version = u16::from_le_bytes(reader.read_array().map_err(truncated)?);
+        if format_version > PORTABLE_FORMAT_VERSION {
+            return Err(ImportError::UnsupportedVersion(format_version).into());
+        }
+        let memory_cost = u32::from_le_bytes(reader.read_array().map_err(truncated)?);
+        let time_cost = u32::from_le_bytes(reader.read_array().map_err(truncated)?);
+        let parallelism = u32::from_le_bytes(reader.read_array().map_err(truncated)?);
+        let [salt_len] = reader.read_array::<1>().map_err(truncated)?;
+        let salt = reader.read_slice(salt_len as usize).map_err(truncated)?;
+        let (header, sealed) = bytes.split_at(reader.position);
+
+        // Out-of-range parameters are rejected here so a crafted file can't demand huge amounts of memory
+        let key_derivation = KeyDerivation::new(memory_cost, time_cost, parallelism)
+            .map_err(|e| ImportError::Malformed(e.to_string()))?;
+        let key = key_derivation.derive_key(password.as_bytes(), salt)
+            .map_err(|e| ImportError::Malformed(e.to_string()))?;
+
+        let sealed: EncryptedData = serde_cbor::from_slice(sealed)
+            .map_err(|e| ImportError::Malformed(e.to_string()))?;
+        let mut payload = KeyHierarchy::decrypt_with_key(key.as_ref(), &sealed, header)
+            .map_err(|_| ImportError::IncorrectPassword)?;
+        let vault = serde_cbor::from_slice(&payload)
+            .map_err(|e| ImportError::Malformed(e.to_string()));
+        payload.zeroize();
+
+        Ok(vault?)
+    }
+}
@@ -0,0 +1,215 @@
+// password_manager_backend/src/_vault/vault_format.rs
+
+use serde_cbor::Value;
+use crate::_vault::{CURRENT_FORMAT_VERSION, FILE_MAC_LENGTH, FIRST_MAC_FORMAT_VERSION, LEGACY_FORMAT_VERSION, VAULT_MAGIC};
+use crate::_vault::migration;
+use crate::_vault::vault_manager::VaultManager;
+use crate::crypto::{KeyDerivation, KeyHierarchy};
+use crate::error::app_error::AppError;
+
+/// Self-describing header written in front of every vault file.
+///
+/// On-disk layout (all integers little-endian):
+/// ```markdown
+/// | magic "PAWPASS\0" | 8 bytes           |
+/// | format version    | u16               |
+/// | memory cost       | u32               |
+/// | time cost         | u32               |
+/// | parallelism       | u32               |
+/// | salt length       | u8                |
+/// | salt              | salt length bytes |
+/// | payload length    | u64               |
+/// | payload (CBOR)    | payload length    |
+/// | MAC (v5+)         | 32 bytes          |
+/// ```
+/// The MAC is an HMAC-SHA256 over everything before it, keyed from the MEK,
+/// so it can only be checked once the vault has been unlocked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultHeader {
+    pub format_version: u16,
+    pub key_derivation: KeyDerivation,
+    pub salt: Vec<u8>,
+}
+
+/// MAC read from a vault file together with the bytes it covers, kept until login can check it
+#[derive(Debug, Clone)]
+pub struct FileMac {
+    pub covered: Vec<u8>,
+    pub tag: [u8; FILE_MAC_LENGTH],
+}
+
+impl FileMac {
+    /// Whether `key_hierarchy` produced this MAC over the covered bytes
+    pub fn verify(&self, key_hierarchy: &KeyHierarchy) -> Result<bool, AppError> {
+        Ok(key_hierarchy.verify_file_mac(&self.covered, &self.tag)?)
+    }
+}
+
+/// The pieces of a vault file before the payload is deserialized
+pub struct ParsedVault<'a> {
+    // `None` when the file predates the header (legacy format)
+    pub header: Option<VaultHeader>,
+    pub payload: &'a [u8],
+    // `None` when the format version predates the MAC
+    pub file_mac: Option<FileMac>,
+}
+
+/// Result of reading a vault file, including the version it was stored with
+pub struct DecodedVault {
+    pub vault_manager: VaultManager,
+    // Version found on disk before any migrations ran
+    pub original_version: u16,
+}
+
+impl DecodedVault {
+    /// True when the file was written by an older version and has been upgraded in memory
+    pub fn was_migrated(&self) -> bool {
+        self.original_version < CURRENT_FORMAT_VERSION
+    }
+}
+
+impl VaultHeader {
+    /// Builds the header describing the current state of a vault
+    pub fn for_vault(vault_manager: &VaultManager) -> Self {
+        Self {
+            format_version: CURRENT_FORMAT_VERSION,
+            key_derivation: vault_manager.auth_service.key_derivation().clone(),
+            salt: vault_manager.auth_service.salt().to_vec(),
+        }
+    }
+
+    /// Writes the header followed by the payload into a single buffer
+    pub fn encode(&self, payload: &[u8]) -> Result<Vec<u8>, AppError> {
+        let salt_len = u8::try_from(self.salt.len())
+            .map_err(|_| AppError::InvalidVaultHeader("Salt is too long to encode".to_string()))?;
+
+        let mut bytes = Vec::with_capacity(31 + self.salt.len() + payload.len());
+        bytes.extend_from_slice(&VAULT_MAGIC);
+        bytes.extend_from_slice(&self.format_version.to_le_bytes());
+        bytes.extend_from_slice(&self.key_derivation.memory_cost().to_le_bytes());
+        bytes.extend_from_slice(&self.key_derivation.time_cost().to_le_bytes());
+        bytes.extend_from_slice(&self.key_derivation.parallelism().to_le_bytes());
+        bytes.push(salt_len);
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(payload);
+
+        Ok(bytes)
+    }
+
+    /// Splits a vault file into its header, payload and MAC
+    pub fn parse(bytes: &[u8]) -> Result<ParsedVault<'_>, AppError> {
+        if !bytes.starts_with(&VAULT_MAGIC) {
+            return Ok(ParsedVault { header: None, payload: bytes, file_mac: None });
+        }
+
+        let mut reader = HeaderReader { bytes, position: VAULT_MAGIC.len() };
+
+        let format_version = u16::from_le_bytes(reader.read_array()?);
+        let memory_cost = u32::from_le_bytes(reader.read_array()?);
+        let time_cost = u32::from_le_bytes(reader.read_array()?);
+        let parallelism = u32::from_le_bytes(reader.read_array()?);
+        let [salt_len] = reader.read_array::<1>()?;
+        let salt = reader.read_slice(salt_len as usize)?.to_vec();
+        let payload_len = u64::from_le_bytes(reader.read_array()?);
+
+        let payload_len = usize::try_from(payload_len)
+            .map_err(|_| AppError::InvalidVaultHeader("Payload length out of range".to_string()))?;
+        let payload = reader.read_slice(payload_len)?;
+
+        let file_mac = if format_version >= FIRST_MAC_FORMAT_VERSION {
+            let covered = bytes[..reader.position].to_vec();
+            let tag = reader.read_array::<FILE_MAC_LENGTH>()?;
+            Some(FileMac { covered, tag })
+        } else {
+            None
+        };
+
+        if reader.position != bytes.len() {
+            return Err(AppError::InvalidVaultHeader("Unexpected data after vault payload".to_string()));
+        }
+
+        let key_derivation = KeyDerivation::new(memory_cost, time_cost, parallelism)
+            .map_err(|e| AppError::InvalidVaultHeader(e.to_string()))?;
+
+        Ok(ParsedVault {
+            header: Some(Self { format_version, key_derivation, salt }),
+            payload,
+            file_mac,
+        })
+    }
+}
+
+/// Serializes a vault manager into the current on-disk format and signs it with the MEK-derived MAC key
+pub fn encode_vault(vault_manager: &VaultManager, key_hierarchy: &KeyHierarchy) -> Result<Vec<u8>, AppError> {
+    let payload = serde_cbor::to_vec(vault_manager)
+        .map_err(|e| AppError::SerializationFailed(e.to_string()))?;
+
+    let mut bytes = VaultHeader::for_vault(vault_manager).encode(&payload)?;
+    let tag = key_hierarchy.file_mac(&bytes)?;
+    bytes.extend_from_slice(&tag);
+
+    Ok(bytes)
+}
+
+/// Reads a vault file of any supported version, running migrations up to the current format
+pub fn decode_vault(bytes: &[u8]) -> Result<DecodedVault, AppError> {
+    let ParsedVault { header, payload, file_mac } = VaultHeader::parse(bytes)?;
+    let original_version = header
+        .as_ref()
+        .map(|h| h.format_version)
+        .unwrap_or(LEGACY_FORMAT_VERSION);
+
+    if original_version > CURRENT_FORMAT_VERSION {
+        return Err(AppError::UnsupportedVaultVersion(original_version));
+    }
+
+    let value: Value = serde_cbor::from_slice(payload)
+        .map_err(|e| AppError::DeserializationFailed(e.to_string()))?;
+
+    let value = migration::migrate_to_current(original_version, value)?;
+
+    let mut vault_manager: VaultManager = serde_cbor::value::from_value(value)
+        .map_err(|e| AppError::DeserializationFailed(e.to_string()))?;
+
+    // The header duplicates the unlock parameters so tools can read them without parsing CBOR,
+    // but the two copies must never disagree
+    if let Some(header) = header {
+        if header.key_derivation != *vault_manager.auth_service.key_derivation()
+            || header.salt != vault_manager.auth_service.salt()
+        {
+            return Err(AppError::DataCorruption(
+                "Vault header does not match the stored key derivation settings".to_string()
+            ));
+        }
+    }
+
+    vault_manager.set_file_mac(file_mac);
+
+    Ok(DecodedVault { vault_manager, original_version })
+}
+
+// Small cursor used to walk the header without panicking on truncated files
+pub(crate) struct HeaderReader<'a> {
+    pub(crate) bytes: &'a [u8],
+    pub(crate) position: usize,
+}
+
+impl<'a> HeaderReader<'a> {
+    pub(crate) fn read_slice(&mut self, len: usize) -> Result<&'a [u8], AppError> {
+        let end = self.position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| AppError::InvalidVaultHeader("Vault file is truncated".to_string()))?;
+
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    pub(crate) fn read_array<const N: usize>(&mut self) -> Result<[u8; N], AppError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_slice(N)?);
+        Ok(array)
+    }
+}
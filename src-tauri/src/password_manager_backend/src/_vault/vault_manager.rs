@@ -1,19 +1,81 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+use crate::_vault::auto_lock::AutoLockSettings;
+use crate::_vault::backup::{AutoBackupSettings, BackupRetentionPolicy};
 use crate::_vault::password_generation::PasswordGenerator;
+use crate::_vault::vault_format::FileMac;
+use crate::_vault::FIRST_MAC_FORMAT_VERSION;
+use crate::audit::strength::PasswordPolicy;
 use crate::auth::auth_service::AuthService;
-use crate::crypto::{KeyDerivation, KeyHierarchy, SecureMemory};
+use crate::error::auth_error::AuthError;
+use crate::auth::unlock_throttle::UnlockPolicy;
+use crate::crypto::{EncryptedData, KeyDerivation, KeyHierarchy, SecureMemory};
 use crate::entry::collection::EntryCollection;
 use crate::error::vault_error::VaultError;
 
 #[derive(Serialize, Deserialize)]
 pub struct VaultManager {
     pub auth_service: AuthService,
+
+    // Entries, categories and favorites only exist in memory while the vault is unlocked.
+    // On disk they live inside `sealed_entries`, so nothing but the unlock material is readable.
+    #[serde(skip, default = "EntryCollection::new")]
     pub entry_collection: EntryCollection,
+    sealed_entries: Option<EncryptedData>,
+
+    // Plaintext collection carried over from vaults written before sealing existed.
+    // It is sealed on the first save after login and then dropped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    legacy_entry_collection: Option<EntryCollection>,
+
+    // Set once `entry_collection` holds the real decrypted data
+    #[serde(skip)]
+    unsealed: bool,
+
+    // MAC read from disk; it needs the MEK, so it is checked by `login` rather than on load
+    #[serde(skip)]
+    file_mac: Option<FileMac>,
+
+    // The settings below are sealed into `sealed_settings` on save. Older vaults stored them in
+    // the clear, so they are still read from there and sealed on the next save.
+    #[serde(default)]
+    sealed_settings: Option<EncryptedData>,
+
+    #[serde(default, skip_serializing)]
     pub key_derivation_settings: KeyDerivation,
+    #[serde(default, skip_serializing)]
     pub password_generator_settings: PasswordGenerator,
     pub initialized: bool,
+    #[serde(default, skip_serializing)]
     pub last_backup_time: DateTime<Utc>,
+    #[serde(default, skip_serializing)]
+    pub unlock_policy: UnlockPolicy,
+    #[serde(default, skip_serializing)]
+    pub auto_lock_settings: AutoLockSettings,
+    #[serde(default, skip_serializing)]
+    pub backup_retention: BackupRetentionPolicy,
+    #[serde(default, skip_serializing)]
+    pub auto_backup_settings: AutoBackupSettings,
+    #[serde(default, skip_serializing)]
+    pub password_policy: PasswordPolicy,
+    // Keyed digest of the contents at the last backup, so unchanged vaults aren't backed up again
+    #[serde(default, skip_serializing)]
+    backup_digest: Option<[u8; 32]>,
+}
+
+// What goes into `sealed_settings`; field by field the same as the plaintext copies in `VaultManager`
+#[derive(Serialize, Deserialize)]
+struct SealedSettings {
+    key_derivation_settings: KeyDerivation,
+    password_generator_settings: PasswordGenerator,
+    last_backup_time: DateTime<Utc>,
+    unlock_policy: UnlockPolicy,
+    auto_lock_settings: AutoLockSettings,
+    backup_retention: BackupRetentionPolicy,
+    auto_backup_settings: AutoBackupSettings,
+    password_policy: PasswordPolicy,
+    backup_digest: Option<[u8; 32]>,
 }
 
 impl VaultManager {
@@ -24,10 +86,21 @@ impl VaultManager {
         let manager = Self {
             auth_service,
             entry_collection: EntryCollection::new(),
+            sealed_entries: None,
+            legacy_entry_collection: None,
+            sealed_settings: None,
+            unsealed: true,
+            file_mac: None,
             key_derivation_settings: KeyDerivation::default(),
             password_generator_settings: PasswordGenerator::default(),
             initialized: true,
             last_backup_time: Default::default(),
+            unlock_policy: UnlockPolicy::default(),
+            auto_lock_settings: AutoLockSettings::default(),
+            backup_retention: BackupRetentionPolicy::default(),
+            auto_backup_settings: AutoBackupSettings::default(),
+            password_policy: PasswordPolicy::default(),
+            backup_digest: None,
         };
         println!("New VaultManager created");
 
@@ -39,13 +112,162 @@ impl VaultManager {
         self.validate_vault_active()?;
         println!("VaultManager::login() called, validation passed");
 
+        // Captured before unlock, which re-wraps older MEKs for the current format
+        let mek_wrap_version = self.auth_service.mek_wrap_version();
+
         println!("VaultManager::login() called, unlocking auth service");
         let key_hierarchy = self.auth_service.unlock(master_password)?;
         println!("VaultManager::login() called, auth service unlocked");
 
+        self.open(&key_hierarchy, mek_wrap_version)?;
+
         Ok(key_hierarchy)
     }
 
+    /// Opens the vault with a key hierarchy obtained without the master password (quick unlock).
+    /// The keys must belong to this vault; a master password change since they were wrapped fails here.
+    pub fn login_with_key_hierarchy(&mut self, key_hierarchy: &KeyHierarchy) -> Result<(), VaultError> {
+        self.validate_vault_active()?;
+
+        if !self.auth_service.verify_master_key(key_hierarchy.master_key.as_ref())? {
+            return Err(AuthError::QuickUnlockUnavailable.into());
+        }
+
+        self.open(key_hierarchy, self.auth_service.mek_wrap_version())
+    }
+
+    // Shared tail of every login: integrity check, then decrypt the entries
+    fn open(&mut self, key_hierarchy: &KeyHierarchy, mek_wrap_version: Option<u16>) -> Result<(), VaultError> {
+        self.verify_file_mac(key_hierarchy, mek_wrap_version)?;
+        println!("VaultManager::open() called, file integrity verified");
+
+        self.unseal_entries(key_hierarchy)?;
+        println!("VaultManager::open() called, entries unsealed");
+
+        let rebound = self.entry_collection.rebind_sensitive_data(key_hierarchy)?;
+        if rebound > 0 {
+            println!("VaultManager::open() called, bound {} entries to their ids", rebound);
+        }
+
+        Ok(())
+    }
+
+    /// Encrypts the whole entry collection and the settings with the MEK so they can be written to disk.
+    /// Must only be called on an unlocked vault; sealing an empty placeholder would wipe the entries.
+    pub fn seal_entries(&mut self, key_hierarchy: &KeyHierarchy) -> Result<(), VaultError> {
+        if !self.unsealed {
+            return Err(VaultError::VaultLocked);
+        }
+
+        let serialized = SecureMemory::new(serde_cbor::to_vec(&self.entry_collection)?);
+        self.sealed_entries = Some(key_hierarchy.encrypt_data(serialized.as_ref(), &self.entries_aad())?);
+        self.legacy_entry_collection = None;
+
+        let settings = SealedSettings {
+            key_derivation_settings: self.key_derivation_settings.clone(),
+            password_generator_settings: self.password_generator_settings.clone(),
+            last_backup_time: self.last_backup_time,
+            unlock_policy: self.unlock_policy.clone(),
+            auto_lock_settings: self.auto_lock_settings,
+            backup_retention: self.backup_retention,
+            auto_backup_settings: self.auto_backup_settings,
+            password_policy: self.password_policy,
+            backup_digest: self.backup_digest,
+        };
+        let serialized = serde_cbor::to_vec(&settings)?;
+        self.sealed_settings = Some(key_hierarchy.encrypt_data(&serialized, &self.settings_aad())?);
+
+        Ok(())
+    }
+
+    /// Decrypts the sealed entry collection and settings, and rebuilds the in-memory search index
+    pub fn unseal_entries(&mut self, key_hierarchy: &KeyHierarchy) -> Result<(), VaultError> {
+        if let Some(sealed) = &self.sealed_settings {
+            let decrypted = key_hierarchy.decrypt_data(sealed, &self.settings_aad())?;
+            let settings: SealedSettings = serde_cbor::from_slice(&decrypted)?;
+            self.key_derivation_settings = settings.key_derivation_settings;
+            self.password_generator_settings = settings.password_generator_settings;
+            self.last_backup_time = settings.last_backup_time;
+            self.unlock_policy = settings.unlock_policy;
+            self.auto_lock_settings = settings.auto_lock_settings;
+            self.backup_retention = settings.backup_retention;
+            self.auto_backup_settings = settings.auto_backup_settings;
+            self.password_policy = settings.password_policy;
+            self.backup_digest = settings.backup_digest;
+        }
+
+        let mut entry_collection = match (&self.sealed_entries, self.legacy_entry_collection.take()) {
+            (Some(sealed), _) => {
+                let decrypted = SecureMemory::new(key_hierarchy.decrypt_data(sealed, &self.entries_aad())?);
+                serde_cbor::from_slice::<EntryCollection>(decrypted.as_ref())?
+            }
+            (None, Some(legacy)) => legacy,
+            (None, None) => EntryCollection::new(),
+        };
+
+        entry_collection.rebuild_search_index();
+        self.entry_collection = entry_collection;
+        self.unsealed = true;
+
+        Ok(())
+    }
+
+    /// Number of entries in a vault read from disk, such as a backup, without unsealing it.
+    /// `None` if `key_hierarchy` can't decrypt them.
+    pub fn count_sealed_entries(&self, key_hierarchy: &KeyHierarchy) -> Option<usize> {
+        match (&self.sealed_entries, &self.legacy_entry_collection) {
+            (Some(sealed), _) => {
+                let decrypted = SecureMemory::new(key_hierarchy.decrypt_data(sealed, &self.entries_aad()).ok()?);
+                serde_cbor::from_slice::<EntryCollection>(decrypted.as_ref())
+                    .ok()
+                    .map(|collection| collection.entries.len())
+            }
+            (None, Some(legacy)) => Some(legacy.entries.len()),
+            (None, None) => Some(0),
+        }
+    }
+
+    /// Whether this vault was read from a file carrying a MAC (as opposed to an older, unsigned format)
+    pub fn has_file_mac(&self) -> bool {
+        self.file_mac.is_some()
+    }
+
+    pub(crate) fn set_file_mac(&mut self, file_mac: Option<FileMac>) {
+        self.file_mac = file_mac;
+    }
+
+    /// Records a backup of the current contents taken at `timestamp`.
+    /// Returns the previous record, to put back with `restore_backup_record` if the backup isn't written.
+    pub(crate) fn record_backup(&mut self, timestamp: DateTime<Utc>, key_hierarchy: &KeyHierarchy) -> (DateTime<Utc>, Option<[u8; 32]>) {
+        let previous = (self.last_backup_time, self.backup_digest);
+        self.last_backup_time = timestamp;
+        self.backup_digest = self.content_digest(key_hierarchy).ok();
+        previous
+    }
+
+    pub(crate) fn restore_backup_record(&mut self, (timestamp, digest): (DateTime<Utc>, Option<[u8; 32]>)) {
+        self.last_backup_time = timestamp;
+        self.backup_digest = digest;
+    }
+
+    // MAC over every entry, category and generator profile in a stable order, plus the salt, which changes with the master password
+    // and key derivation. Entry secrets are already ciphertext, so nothing is decrypted to compute it.
+    fn content_digest(&self, key_hierarchy: &KeyHierarchy) -> Result<[u8; 32], VaultError> {
+        let mut entries: Vec<_> = self.entry_collection.entries.values().collect();
+        entries.sort_unstable_by_key(|entry| entry.id);
+        let mut categories = self.entry_collection.categories.get_all_categories();
+        categories.sort_unstable_by_key(|category| category.id);
+        let profiles = self.entry_collection.generator_profiles.list();
+
+        let serialized = serde_cbor::to_vec(&(self.auth_service.salt(), entries, categories, profiles))?;
+        Ok(key_hierarchy.file_mac(&serialized)?)
+    }
+
+    /// Whether the entry collection currently holds decrypted data
+    pub fn is_unsealed(&self) -> bool {
+        self.unsealed
+    }
+
     pub fn update_key_derivation(
         &mut self, 
         master_password: SecureMemory<String>, 
@@ -67,6 +289,62 @@ impl VaultManager {
         Ok(key_hierarchy)
     }
     
+    pub fn update_unlock_policy(&mut self, policy: UnlockPolicy) -> Result<(), VaultError> {
+        self.validate_vault_active()?;
+        policy.validate()?;
+        self.unlock_policy = policy;
+        Ok(())
+    }
+
+    pub fn update_auto_lock_settings(&mut self, settings: AutoLockSettings) -> Result<(), VaultError> {
+        self.validate_vault_active()?;
+        settings.validate()?;
+        self.auto_lock_settings = settings;
+        Ok(())
+    }
+
+    pub fn update_backup_retention(&mut self, policy: BackupRetentionPolicy) -> Result<(), VaultError> {
+        self.validate_vault_active()?;
+        policy.validate()?;
+        self.backup_retention = policy;
+        Ok(())
+    }
+
+    pub fn update_auto_backup_settings(&mut self, settings: AutoBackupSettings) -> Result<(), VaultError> {
+        self.validate_vault_active()?;
+        settings.validate()?;
+        self.auto_backup_settings = settings;
+        Ok(())
+    }
+
+    pub fn update_password_policy(&mut self, policy: PasswordPolicy) -> Result<(), VaultError> {
+        self.validate_vault_active()?;
+        policy.validate()?;
+        self.password_policy = policy;
+        Ok(())
+    }
+
+    /// Whether the entries, categories or unlock credentials differ from the last backup
+    pub fn changed_since_backup(&self, key_hierarchy: &KeyHierarchy) -> bool {
+        match (self.backup_digest, self.content_digest(key_hierarchy)) {
+            (Some(backed_up), Ok(current)) => backed_up != current,
+            _ => true,
+        }
+    }
+
+    /// Whether the backup interval has elapsed since the last backup and there is something new to back up
+    pub fn auto_backup_due(&self, key_hierarchy: &KeyHierarchy, now: DateTime<Utc>) -> bool {
+        match self.auto_backup_settings.interval() {
+            Some(interval) => now - self.last_backup_time >= interval && self.changed_since_backup(key_hierarchy),
+            None => false,
+        }
+    }
+
+    /// Whether a snapshot should be taken before a change that can't be undone
+    pub fn snapshot_due(&self, key_hierarchy: &KeyHierarchy) -> bool {
+        self.auto_backup_settings.snapshot_before_changes && self.changed_since_backup(key_hierarchy)
+    }
+
     pub fn change_master_password(
         &mut self, 
         old_password: SecureMemory<String>, 
@@ -82,6 +360,38 @@ impl VaultManager {
 
 impl VaultManager {
 
+    // Associated data for the sealed collection, so it only opens inside the vault that wrote it
+    fn entries_aad(&self) -> Vec<u8> {
+        format!("pawpass/entries/{}", self.auth_service.vault_id()).into_bytes()
+    }
+
+    fn settings_aad(&self) -> Vec<u8> {
+        format!("pawpass/settings/{}", self.auth_service.vault_id()).into_bytes()
+    }
+
+    // A signed file must match its MAC. An unsigned file is only accepted if its MEK wrap also predates
+    // the MAC; a wrap bound to a newer format means the trailer was stripped to downgrade the file.
+    fn verify_file_mac(&mut self, key_hierarchy: &KeyHierarchy, mek_wrap_version: Option<u16>) -> Result<(), VaultError> {
+        match &self.file_mac {
+            Some(file_mac) => {
+                let valid = file_mac
+                    .verify(key_hierarchy)
+                    .map_err(|e| VaultError::AppError(e.to_string()))?;
+                if !valid {
+                    return Err(VaultError::VaultTampered);
+                }
+            }
+            None => {
+                if mek_wrap_version.is_some_and(|version| version >= FIRST_MAC_FORMAT_VERSION) {
+                    return Err(VaultError::VaultTampered);
+                }
+            }
+        }
+
+        self.file_mac = None;
+        Ok(())
+    }
+
     fn validate_vault_active(&self) -> Result<(), VaultError> {
         if !self.initialized {
             return Err(VaultError::VaultNotInitialized);
@@ -89,3 +399,14 @@ impl VaultManager {
         Ok(())
     }
 }
+
+// Clears decrypted entries and unlock material before the manager is dropped on lock
+impl Zeroize for VaultManager {
+    fn zeroize(&mut self) {
+        self.auth_service.zeroize();
+        for entry in self.entry_collection.entries.values_mut() {
+            entry.zeroize();
+        }
+        self.unsealed = false;
+    }
+}
@@ -1,11 +1,20 @@
-use std::path::PathBuf;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use tokio::sync::RwLock;
 use uuid::Uuid;
+use crate::auth::unlock_throttle::UnlockThrottle;
+use crate::crypto::{EncryptedData, KeyHierarchy, SecureMemory};
+use crate::error::auth_error::AuthError;
 use crate::error::app_error::AppError;
+use crate::error::vault_error::VaultError;
 
 
+use super::backup::{parse_backup_file_name, BackupInfo, BackupRetentionPolicy};
+use super::vault_format::{decode_vault, encode_vault};
+use super::{BACKUP_FILE_PREFIX, BACKUP_TIMESTAMP_FORMAT};
 use super::vault_manager::VaultManager;
 
 
@@ -14,6 +23,7 @@ pub struct VaultStorage {
     pub vault_path: PathBuf,          // Where the main vault file lives
     pub backup_dir: PathBuf,          // Directory for backup files
     pub temp_dir: PathBuf,            // Directory for temporary files during atomic saves
+    pub attachments_dir: PathBuf,     // Encrypted attachment files, one per attachment id
 
     // Concurrency control
     pub file_lock: Arc<RwLock<()>>,   // Prevents concurrent file access
@@ -25,11 +35,13 @@ impl VaultStorage {
         let vault_path = app_data_dir.join("PawPass");
         let backup_dir = vault_path.join("backups");
         let temp_dir = vault_path.join("temp");
+        let attachments_dir = vault_path.join("attachments");
 
         Self {
             vault_path,
             backup_dir,
             temp_dir,
+            attachments_dir,
             file_lock: Arc::new(RwLock::new(())),
         }
     }
@@ -50,20 +62,31 @@ impl VaultStorage {
             .map_err(|e| AppError::from(e))?;
         println!("Vault data read");
 
-        // Deserialize the vault data
-        let vault_manager: VaultManager = serde_cbor::from_slice(&vault_data)
-            .map_err(|e| AppError::DeserializationFailed(e.to_string()))?;
-        println!("Vault deserialized"); 
+        // Parse the header and upgrade older formats step by step
+        let decoded = decode_vault(&vault_data)?;
+        println!("Vault deserialized (format v{})", decoded.original_version);
 
-        Ok(vault_manager)
+        // Keep the untouched original around until the upgraded vault has been saved successfully
+        if decoded.was_migrated() {
+            self.preserve_pre_migration_copy(&vault_data, decoded.original_version).await?;
+        }
+
+        Ok(decoded.vault_manager)
     }
 
 
-    pub async fn save_vault(&self, vault_manager: &VaultManager) -> Result<(), AppError> {
+    pub async fn save_vault(
+        &self,
+        vault_manager: &mut VaultManager,
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<(), AppError> {
         // Use write lock instead of read lock since saving is a critical operation
         // that shouldn't happen concurrently with other saves or loads
         let _guard = self.file_lock.write().await;
 
+        // Seal the latest entries before anything touches the disk
+        vault_manager.seal_entries(key_hierarchy)?;
+
         // Ensure directories exist first
         self.ensure_directories().await?;
 
@@ -74,8 +97,7 @@ impl VaultStorage {
         let final_path = self.vault_path.join("vault.dat");
 
         // Serialize and write in separate steps for better error handling
-        let serialized = serde_cbor::to_vec(&vault_manager)
-            .map_err(|e| AppError::SerializationFailed(e.to_string()))?;
+        let serialized = encode_vault(vault_manager, key_hierarchy)?;
 
         // Write to temporary file with specific permissions
         tokio::fs::write(&temp_path, &serialized)
@@ -108,24 +130,71 @@ impl VaultStorage {
     }
 
 
-    pub async fn create_backup(&self, vault_manager: &mut VaultManager) -> Result<(), AppError> {
+    pub async fn create_backup(
+        &self,
+        vault_manager: &mut VaultManager,
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<(), AppError> {
         // Acquire write lock to ensure consistency during backup
         let _guard = self.file_lock.write().await;
 
+        self.write_backup(vault_manager, key_hierarchy).await?;
+
+        // Pruning problems shouldn't fail a backup that was written successfully
+        if let Err(e) = self.remove_expired_backups(&vault_manager.backup_retention).await {
+            println!("Failed to prune old backups: {}", e);
+        }
+
+        Ok(())
+    }
 
+    /// Takes a backup without applying the retention policy, for snapshots taken right before
+    /// another backup is read, which pruning could otherwise delete
+    pub async fn create_backup_without_pruning(
+        &self,
+        vault_manager: &mut VaultManager,
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<(), AppError> {
+        let _guard = self.file_lock.write().await;
+        self.write_backup(vault_manager, key_hierarchy).await
+    }
+
+    // Callers hold the write lock
+    async fn write_backup(
+        &self,
+        vault_manager: &mut VaultManager,
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<(), AppError> {
         // Create backup filename with timestamp for easy identification
         let timestamp = Utc::now();
 
-        vault_manager.last_backup_time = timestamp;
+        // Recorded before sealing so the backup file carries its own record, and undone if the write
+        // fails so the scheduler retries instead of waiting out another interval
+        let previous_record = vault_manager.record_backup(timestamp, key_hierarchy);
+        let result = async {
+            vault_manager.seal_entries(key_hierarchy)?;
+            self.write_backup_file(vault_manager, key_hierarchy, timestamp).await
+        }.await;
+        if result.is_err() {
+            vault_manager.restore_backup_record(previous_record);
+        }
+        result
+    }
 
+    async fn write_backup_file(
+        &self,
+        vault_manager: &VaultManager,
+        key_hierarchy: &KeyHierarchy,
+        timestamp: DateTime<Utc>,
+    ) -> Result<(), AppError> {
         let backup_filename = format!(
-            "vault_backup_{}.dat",
-            timestamp.format("%Y%m%d_%H%M%S")
+            "{}{}.dat",
+            BACKUP_FILE_PREFIX,
+            timestamp.format(BACKUP_TIMESTAMP_FORMAT)
         );
         let backup_path = self.backup_dir.join(backup_filename);
         // Serialize vault manager
-        let serialized = serde_cbor::to_vec(vault_manager)
-            .map_err(|e| AppError::SerializationFailed(e.to_string()))?;
+        let serialized = encode_vault(vault_manager, key_hierarchy)?;
 
         // Ensure backup directory exists
         self.ensure_directories().await?;
@@ -140,6 +209,14 @@ impl VaultStorage {
                 e.to_string(),
             ))?;
 
+        // Copy the attachments first so a backup file never exists without its files
+        self.copy_attachments(&self.attachments_dir, &Self::backup_attachments_dir(&backup_path))
+            .await
+            .map_err(|e| AppError::BackupCreationFailed(
+                backup_path.to_string_lossy().to_string(),
+                e.to_string(),
+            ))?;
+
         // Move temporary backup to final location
         tokio::fs::rename(&temp_backup, &backup_path)
             .await
@@ -148,10 +225,7 @@ impl VaultStorage {
                 e.to_string()
             ))?;
 
-        self.cleanup_temp_files().await?;
-
-
-        Ok(())
+        self.cleanup_temp_files().await
     }
 
     pub async fn restore_from_file(&self, backup_path: PathBuf) -> Result<VaultManager, AppError> {
@@ -170,19 +244,16 @@ impl VaultStorage {
                 e.to_string(),
             ))?;
     
-        let restored_vault: VaultManager = serde_cbor::from_slice(&backup_data)
-            .map_err(|e| AppError::DeserializationFailed(e.to_string()))?;
+        // Backups may have been written by an older version, so make sure they decode like a normal load
+        let restored_vault = decode_vault(&backup_data)?.vault_manager;
     
         // Acquire the write lock for atomic access
         let _guard = self.file_lock.write().await;
     
-        // Write the restored vault to disk
-        let serialized = serde_cbor::to_vec(&restored_vault)
-            .map_err(|e| AppError::SerializationFailed(e.to_string()))?;
-    
-        // Write to temporary file first
+        // The backup is written back untouched: re-encoding would need the MEK to sign the file,
+        // and older formats are migrated by the next load anyway
         let temp_file = self.temp_dir.join(format!("vault_{}.tmp", uuid::Uuid::new_v4()));
-        tokio::fs::write(&temp_file, &serialized)
+        tokio::fs::write(&temp_file, &backup_data)
             .await
             .map_err(|e| AppError::TemporaryFileError(e.to_string()))?;
     
@@ -193,15 +264,461 @@ impl VaultStorage {
                 backup_path.to_string_lossy().to_string(),
                 e.to_string(),
             ))?;
+
+        self.restore_attachments(&backup_path).await?;
     
         self.cleanup_temp_files().await?;
         Ok(restored_vault)
     }
 
+    /// Reads a backup and unlocks it with its own master password, leaving the current vault untouched
+    pub async fn open_backup(
+        &self,
+        backup_path: &Path,
+        master_password: SecureMemory<String>,
+    ) -> Result<(VaultManager, KeyHierarchy), AppError> {
+        if !backup_path.exists() {
+            return Err(AppError::PathNotFound(
+                backup_path.to_string_lossy().to_string()
+            ));
+        }
+
+        let _guard = self.file_lock.read().await;
+        let backup_data = tokio::fs::read(backup_path)
+            .await
+            .map_err(|e| AppError::BackupRestoreFailed(
+                backup_path.to_string_lossy().to_string(),
+                e.to_string(),
+            ))?;
+
+        let mut vault_manager = decode_vault(&backup_data)?.vault_manager;
+        // A wrong password is reported like a failed login instead of a generic vault error
+        let key_hierarchy = vault_manager.login(master_password).map_err(|e| match e {
+            VaultError::AuthError(auth_error) => AppError::from(auth_error),
+            other => AppError::from(other),
+        })?;
+        Ok((vault_manager, key_hierarchy))
+    }
+
     pub async fn vault_exists(&self) -> bool {
         self.vault_path.join("vault.dat").exists()
     }
 
+    /// Loads the failed-unlock counter; a missing file simply means no failures yet
+    pub async fn load_unlock_throttle(&self) -> Result<UnlockThrottle, AppError> {
+        let _guard = self.file_lock.read().await;
+
+        let throttle_path = self.vault_path.join("unlock_throttle.dat");
+        if !throttle_path.exists() {
+            return Ok(UnlockThrottle::default());
+        }
+
+        let data = tokio::fs::read(&throttle_path).await?;
+        serde_cbor::from_slice(&data)
+            .map_err(|e| AppError::DeserializationFailed(e.to_string()))
+    }
+
+    /// Persists the failed-unlock counter. It lives outside `vault.dat` because it has to be
+    /// written after failed logins, when the vault cannot be sealed.
+    pub async fn save_unlock_throttle(&self, throttle: &UnlockThrottle) -> Result<(), AppError> {
+        // Held so `cleanup_temp_files` can't remove the temporary file before the rename
+        let _guard = self.file_lock.write().await;
+
+        self.ensure_directories().await?;
+
+        let serialized = serde_cbor::to_vec(throttle)
+            .map_err(|e| AppError::SerializationFailed(e.to_string()))?;
+
+        let temp_path = self.temp_dir.join(format!("throttle_{}.tmp", Uuid::new_v4()));
+        tokio::fs::write(&temp_path, &serialized)
+            .await
+            .map_err(|e| AppError::TemporaryFileError(e.to_string()))?;
+
+        tokio::fs::rename(&temp_path, self.vault_path.join("unlock_throttle.dat"))
+            .await
+            .map_err(|e| AppError::AtomicSaveFailed(
+                temp_path.to_string_lossy().to_string(),
+                e.to_string()
+            ))
+    }
+
+    /// Replaces a vault that failed its integrity check with the newest backup that unlocks
+    /// with `master_password` and carries a valid MAC. The rejected file is kept in the backup
+    /// directory for inspection. Returns the unlocked backup, ready to be saved.
+    ///
+    /// The caller must have confirmed that the current vault fails its integrity check. Fails with
+    /// `InvalidPassword` when no backup verified and at least one rejected the password, so the
+    /// caller can count the attempt.
+    pub async fn restore_latest_verified_backup(
+        &self,
+        master_password: &SecureMemory<String>,
+    ) -> Result<(VaultManager, KeyHierarchy), AppError> {
+        let _guard = self.file_lock.write().await;
+        self.ensure_directories().await?;
+
+        let mut password_rejected = false;
+        for (backup_path, _) in self.backup_files().await? {
+            let Ok(backup_data) = tokio::fs::read(&backup_path).await else { continue };
+            let Ok(decoded) = decode_vault(&backup_data) else { continue };
+            let mut vault_manager = decoded.vault_manager;
+
+            // Unsigned backups predate the MAC and cannot be verified
+            if !vault_manager.has_file_mac() {
+                continue;
+            }
+
+            let key_hierarchy = match vault_manager.login(SecureMemory::new(master_password.as_ref().clone())) {
+                Ok(key_hierarchy) => key_hierarchy,
+                Err(e) => {
+                    password_rejected |= matches!(e, VaultError::AuthError(AuthError::InvalidPassword));
+                    println!("Backup {} could not be verified", backup_path.display());
+                    continue;
+                }
+            };
+
+            println!("Restoring verified backup {}", backup_path.display());
+            let final_path = self.vault_path.join("vault.dat");
+            if final_path.exists() {
+                let rejected_path = self.backup_dir.join(format!(
+                    "vault_tampered_{}.dat",
+                    Utc::now().format("%Y%m%d_%H%M%S")
+                ));
+                tokio::fs::rename(&final_path, &rejected_path).await?;
+            }
+
+            let temp_file = self.temp_dir.join(format!("vault_{}.tmp", Uuid::new_v4()));
+            tokio::fs::write(&temp_file, &backup_data)
+                .await
+                .map_err(|e| AppError::TemporaryFileError(e.to_string()))?;
+            tokio::fs::rename(&temp_file, &final_path)
+                .await
+                .map_err(|e| AppError::BackupRestoreFailed(
+                    backup_path.to_string_lossy().to_string(),
+                    e.to_string(),
+                ))?;
+
+            self.restore_attachments(&backup_path).await?;
+
+            self.cleanup_temp_files().await?;
+            return Ok((vault_manager, key_hierarchy));
+        }
+
+        if password_rejected {
+            return Err(AuthError::InvalidPassword.into());
+        }
+        Err(AppError::BackupRestoreFailed(
+            self.backup_dir.to_string_lossy().to_string(),
+            "No backup could be verified with this master password".to_string(),
+        ))
+    }
+
+    /// Permanently deletes the vault, its backups and the unlock counter.
+    /// Backups are removed too since they open with the same master password.
+    pub async fn wipe_vault(&self) -> Result<(), AppError> {
+        let _guard = self.file_lock.write().await;
+
+        for file in ["vault.dat", "unlock_throttle.dat"] {
+            let path = self.vault_path.join(file);
+            if path.exists() {
+                tokio::fs::remove_file(&path).await?;
+            }
+        }
+
+        if self.backup_dir.exists() {
+            tokio::fs::remove_dir_all(&self.backup_dir)
+                .await
+                .map_err(|_| AppError::BackupDeletionFailed)?;
+        }
+
+        if self.attachments_dir.exists() {
+            tokio::fs::remove_dir_all(&self.attachments_dir).await?;
+        }
+
+        self.cleanup_temp_files().await
+    }
+
+}
+
+// Backup listing and retention
+impl VaultStorage {
+    // Backup files with the time in their name, newest first
+    async fn backup_files(&self) -> Result<Vec<(PathBuf, DateTime<Utc>)>, AppError> {
+        if !self.backup_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups = Vec::new();
+        let mut dir = tokio::fs::read_dir(&self.backup_dir)
+            .await
+            .map_err(|e| AppError::DirectoryReadFailed(e.to_string()))?;
+        while let Some(entry) = dir.next_entry().await
+            .map_err(|e| AppError::DirectoryReadFailed(e.to_string()))? {
+            if let Some(created_at) = parse_backup_file_name(&entry.file_name().to_string_lossy()) {
+                backups.push((entry.path(), created_at));
+            }
+        }
+        backups.sort_unstable_by_key(|(_, created_at)| Reverse(*created_at));
+        Ok(backups)
+    }
+
+    /// Lists backups newest first. Entry counts are only filled in for backups `key_hierarchy` can decrypt.
+    pub async fn list_backups(&self, key_hierarchy: &KeyHierarchy) -> Result<Vec<BackupInfo>, AppError> {
+        let _guard = self.file_lock.read().await;
+
+        let mut backups = Vec::new();
+        for (path, created_at) in self.backup_files().await? {
+            // One bad backup mustn't hide the good ones, so problems are reported per file
+            let decoded = tokio::fs::read(&path)
+                .await
+                .ok()
+                .and_then(|data| Some((data.len() as u64, decode_vault(&data).ok()?)));
+            let corrupt = decoded.is_none();
+            let (mut size, entry_count) = match decoded {
+                Some((size, decoded)) => (size, decoded.vault_manager.count_sealed_entries(key_hierarchy)),
+                None => (tokio::fs::metadata(&path).await.map_or(0, |metadata| metadata.len()), None),
+            };
+            size += Self::directory_size(&Self::backup_attachments_dir(&path)).await;
+
+            backups.push(BackupInfo {
+                file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                path,
+                created_at,
+                size,
+                entry_count,
+                corrupt,
+            });
+        }
+        Ok(backups)
+    }
+
+    // Bytes in the files directly inside `dir`, counting whatever can be read
+    async fn directory_size(dir: &Path) -> u64 {
+        let Ok(mut entries) = tokio::fs::read_dir(dir).await else { return 0 };
+        let mut size = 0;
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            size += entry.metadata().await.map_or(0, |metadata| metadata.len());
+        }
+        size
+    }
+
+    /// Deletes one backup and its attachment copies. Only names produced by `create_backup` are accepted.
+    pub async fn delete_backup(&self, file_name: &str) -> Result<(), AppError> {
+        let _guard = self.file_lock.write().await;
+
+        // Rejects anything that isn't a plain backup file name, including paths
+        if parse_backup_file_name(file_name).is_none() || Path::new(file_name).file_name() != Some(file_name.as_ref()) {
+            return Err(AppError::PathNotFound(file_name.to_string()));
+        }
+        let backup_path = self.backup_dir.join(file_name);
+        if !backup_path.exists() {
+            return Err(AppError::PathNotFound(backup_path.to_string_lossy().to_string()));
+        }
+
+        self.remove_backup(&backup_path).await
+    }
+
+    // Removes the backups the policy no longer keeps; expects the caller to hold the file lock
+    async fn remove_expired_backups(&self, policy: &BackupRetentionPolicy) -> Result<usize, AppError> {
+        let backups = self.backup_files().await?;
+        let created: Vec<DateTime<Utc>> = backups.iter().map(|(_, created_at)| *created_at).collect();
+
+        let expired = policy.expired(&created);
+        for &index in &expired {
+            self.remove_backup(&backups[index].0).await?;
+        }
+
+        if !expired.is_empty() {
+            println!("Pruned {} old backup(s)", expired.len());
+        }
+        Ok(expired.len())
+    }
+
+    async fn remove_backup(&self, backup_path: &Path) -> Result<(), AppError> {
+        tokio::fs::remove_file(backup_path)
+            .await
+            .map_err(|_| AppError::BackupDeletionFailed)?;
+
+        let attachments_dir = Self::backup_attachments_dir(backup_path);
+        if attachments_dir.exists() {
+            tokio::fs::remove_dir_all(&attachments_dir)
+                .await
+                .map_err(|_| AppError::BackupDeletionFailed)?;
+        }
+        Ok(())
+    }
+}
+
+// Attachment files
+impl VaultStorage {
+    fn attachment_path(&self, attachment_id: &Uuid) -> PathBuf {
+        self.attachments_dir.join(format!("{}.bin", attachment_id))
+    }
+
+    /// Atomically writes an attachment's ciphertext. Call before saving the vault that references it.
+    pub async fn write_attachment(&self, attachment_id: &Uuid, ciphertext: &EncryptedData) -> Result<(), AppError> {
+        let _guard = self.file_lock.write().await;
+        self.ensure_directories().await?;
+
+        let serialized = serde_cbor::to_vec(ciphertext)
+            .map_err(|e| AppError::SerializationFailed(e.to_string()))?;
+
+        let temp_path = self.temp_dir.join(format!("attachment_{}.tmp", Uuid::new_v4()));
+        tokio::fs::write(&temp_path, &serialized)
+            .await
+            .map_err(|e| AppError::TemporaryFileError(e.to_string()))?;
+
+        tokio::fs::rename(&temp_path, self.attachment_path(attachment_id))
+            .await
+            .map_err(|e| AppError::AtomicSaveFailed(
+                temp_path.to_string_lossy().to_string(),
+                e.to_string()
+            ))
+    }
+
+    pub async fn read_attachment(&self, attachment_id: &Uuid) -> Result<EncryptedData, AppError> {
+        let _guard = self.file_lock.read().await;
+
+        let path = self.attachment_path(attachment_id);
+        if !path.exists() {
+            return Err(AppError::PathNotFound(path.to_string_lossy().to_string()));
+        }
+
+        let data = tokio::fs::read(&path).await?;
+        serde_cbor::from_slice(&data)
+            .map_err(|e| AppError::DeserializationFailed(e.to_string()))
+    }
+
+    /// Deletes attachment files; already missing files are ignored
+    pub async fn remove_attachments(&self, attachment_ids: &[Uuid]) -> Result<(), AppError> {
+        let _guard = self.file_lock.write().await;
+
+        for attachment_id in attachment_ids {
+            let path = self.attachment_path(attachment_id);
+            if path.exists() {
+                tokio::fs::remove_file(&path).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes files that no entry references any more, e.g. left behind by a crash between
+    /// saving the vault and removing the file. Returns how many were removed.
+    pub async fn remove_orphaned_attachments(&self, referenced: &HashSet<Uuid>) -> Result<usize, AppError> {
+        let _guard = self.file_lock.write().await;
+
+        if !self.attachments_dir.exists() {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        let mut dir = tokio::fs::read_dir(&self.attachments_dir)
+            .await
+            .map_err(|e| AppError::DirectoryReadFailed(e.to_string()))?;
+        while let Some(entry) = dir.next_entry().await
+            .map_err(|e| AppError::DirectoryReadFailed(e.to_string()))? {
+            let path = entry.path();
+            let attachment_id = path
+                .file_stem()
+                .and_then(|stem| Uuid::parse_str(&stem.to_string_lossy()).ok());
+
+            if attachment_id.is_some_and(|id| !referenced.contains(&id)) {
+                tokio::fs::remove_file(&path).await?;
+                removed += 1;
+            }
+        }
+
+        if removed > 0 {
+            println!("Removed {} orphaned attachment(s)", removed);
+        }
+        Ok(removed)
+    }
+
+    // Attachments of `vault_backup_X.dat` live in `vault_backup_X_attachments/` next to it
+    fn backup_attachments_dir(backup_path: &Path) -> PathBuf {
+        let stem = backup_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        backup_path.with_file_name(format!("{}_attachments", stem))
+    }
+
+    async fn copy_attachments(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        tokio::fs::create_dir_all(to).await?;
+        if !from.exists() {
+            return Ok(());
+        }
+
+        let mut dir = tokio::fs::read_dir(from).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            if entry.file_type().await?.is_file() {
+                tokio::fs::copy(entry.path(), to.join(entry.file_name())).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies attachment files into this vault from beside another vault file: a backup's own
+    /// attachments directory, or the `attachments` directory next to a `vault.dat`. Files already
+    /// present are left alone. Returns the ids whose files couldn't be found.
+    pub async fn import_attachments_from(
+        &self,
+        vault_file: &Path,
+        attachment_ids: &[Uuid],
+    ) -> Result<HashSet<Uuid>, AppError> {
+        let sources = [
+            Self::backup_attachments_dir(vault_file),
+            vault_file.with_file_name("attachments"),
+        ];
+        tokio::fs::create_dir_all(&self.attachments_dir).await?;
+
+        let mut missing = HashSet::new();
+        for attachment_id in attachment_ids {
+            let destination = self.attachment_path(attachment_id);
+            if destination.exists() {
+                continue;
+            }
+
+            let file_name = format!("{}.bin", attachment_id);
+            match sources.iter().map(|dir| dir.join(&file_name)).find(|source| source.is_file()) {
+                Some(source) => {
+                    tokio::fs::copy(&source, &destination).await?;
+                }
+                None => {
+                    missing.insert(*attachment_id);
+                }
+            }
+        }
+        Ok(missing)
+    }
+
+    // Swaps in the attachments saved with a backup. Backups made before attachments existed
+    // have none, so the current files are left for the orphan sweep after the next login.
+    async fn restore_attachments(&self, backup_path: &Path) -> Result<(), AppError> {
+        let source = Self::backup_attachments_dir(backup_path);
+        if !source.is_dir() {
+            return Ok(());
+        }
+
+        let restore_error = |e: std::io::Error| AppError::BackupRestoreFailed(
+            backup_path.to_string_lossy().to_string(),
+            e.to_string(),
+        );
+
+        // Build the new directory beside the old one, then swap them with renames
+        let staged = self.temp_dir.join(format!("attachments_{}", Uuid::new_v4()));
+        self.copy_attachments(&source, &staged).await.map_err(restore_error)?;
+
+        let retired = self.temp_dir.join(format!("attachments_old_{}", Uuid::new_v4()));
+        if self.attachments_dir.exists() {
+            tokio::fs::rename(&self.attachments_dir, &retired).await.map_err(restore_error)?;
+        }
+        tokio::fs::rename(&staged, &self.attachments_dir).await.map_err(restore_error)?;
+
+        if retired.exists() {
+            let _ = tokio::fs::remove_dir_all(&retired).await;
+        }
+        Ok(())
+    }
 }
 
 impl VaultStorage {
@@ -215,6 +732,9 @@ impl VaultStorage {
     pub fn get_temp_dir(&self) -> &PathBuf {
         &self.temp_dir
     }
+    pub fn get_attachments_dir(&self) -> &PathBuf {
+        &self.attachments_dir
+    }
     pub fn get_file_lock(&self) -> Arc<RwLock<()>> {
         self.file_lock.clone()
     }
@@ -226,7 +746,7 @@ impl VaultStorage {
     /// Returns `Ok(())` if successful, or an `AppError` if creation fails
     pub async fn ensure_directories(&self) -> Result<(), AppError> {
         // Iterate through all required directory paths
-        for dir in [&self.vault_path, &self.backup_dir, &self.temp_dir] {
+        for dir in [&self.vault_path, &self.backup_dir, &self.temp_dir, &self.attachments_dir] {
             // create_dir_all() will:
             // 1. Do nothing if the directory already exists
             // 2. Create the directory and all parent directories if they don't exist
@@ -243,6 +763,24 @@ impl VaultStorage {
         Ok(())
     }
 
+    // Copies a vault file that is about to be upgraded into the backup directory
+    // so a failed migration can never leave the user without their original data
+    async fn preserve_pre_migration_copy(&self, vault_data: &[u8], version: u16) -> Result<(), AppError> {
+        let copy_path = self.backup_dir.join(format!("vault_pre_migration_v{}.dat", version));
+
+        // Only the first copy matters; later loads see the same old file until it is re-saved
+        if copy_path.exists() {
+            return Ok(());
+        }
+
+        tokio::fs::write(&copy_path, vault_data)
+            .await
+            .map_err(|e| AppError::BackupCreationFailed(
+                copy_path.to_string_lossy().to_string(),
+                e.to_string(),
+            ))
+    }
+
     // Helper method to clean up old temporary files
     async fn cleanup_temp_files(&self) -> Result<(), AppError> {
         let mut dir = tokio::fs::read_dir(&self.temp_dir)
@@ -0,0 +1,45 @@
+// password_manager_backend/src/_vault/wordlist.rs
+
+use std::sync::LazyLock;
+
+// A dice roll, a tab and the word on each line, as in the EFF wordlists
+const WORDLIST: &str = include_str!("passphrase_wordlist.txt");
+
+/// Words passphrases are built from, parsed once on first use
+pub static WORDS: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    WORDLIST
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| line.split_once('\t').map_or(line, |(_, word)| word).trim())
+        .collect()
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn word_at(dice: &str) -> Option<&'static str> {
+        WORDLIST
+            .lines()
+            .find_map(|line| line.strip_prefix(dice)?.strip_prefix('\t'))
+            .map(str::trim)
+    }
+
+    #[test]
+    fn has_one_unique_word_per_roll_of_five_dice() {
+        assert_eq!(WORDS.len(), 7776);
+        assert_eq!(WORDS.iter().collect::<HashSet<_>>().len(), 7776);
+    }
+
+    #[test]
+    fn matches_the_eff_large_wordlist() {
+        // The file is the EFF list verbatim: no header or other lines of its own
+        assert_eq!(WORDLIST.lines().count(), 7776);
+        assert_eq!(word_at("11111"), Some("abacus"));
+        assert_eq!(word_at("11112"), Some("abdomen"));
+        assert_eq!(word_at("11113"), Some("abdominal"));
+        assert_eq!(word_at("11114"), Some("abide"));
+        assert_eq!(word_at("66666"), Some("zoom"));
+    }
+}
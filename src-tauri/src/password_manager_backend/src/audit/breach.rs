@@ -0,0 +1,159 @@
+// password_manager_backend/src/audit/breach.rs
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use sha1::{Digest, Sha1};
+use crate::error::breach_error::BreachError;
+
+/// SHA-1 of a password, the form the breach list is keyed by
+pub type PasswordHash = [u8; 20];
+
+// Hex digits of the hash that name each file of a range download
+const RANGE_PREFIX_LEN: usize = 5;
+
+enum Layout {
+    // One file of `HASH:COUNT` lines sorted by hash
+    Combined,
+    // A directory of `PREFIX.txt` files holding the `SUFFIX:COUNT` lines of each prefix
+    Ranges,
+}
+
+/// A local copy of the Have I Been Pwned password list, checked without any network access.
+///
+/// Both layouts written by the HIBP downloader are accepted: a single file of upper-case SHA-1
+/// hashes sorted ascending, or a directory with one file per 5-character hash prefix, laid out
+/// like the responses of the k-anonymity range API. The full list is tens of gigabytes, so it is
+/// streamed rather than loaded.
+pub struct BreachList {
+    path: PathBuf,
+    layout: Layout,
+}
+
+pub fn hash_password(password: &str) -> PasswordHash {
+    Sha1::digest(password.as_bytes()).into()
+}
+
+// Parses upper- or lower-case hex split across a range file name and a line
+fn parse_hash(prefix: &str, rest: &str) -> Option<PasswordHash> {
+    let mut digits = prefix.chars().chain(rest.chars());
+    let mut hash = [0u8; 20];
+    for byte in &mut hash {
+        let high = digits.next()?.to_digit(16)?;
+        let low = digits.next()?.to_digit(16)?;
+        *byte = (high << 4 | low) as u8;
+    }
+    digits.next().is_none().then_some(hash)
+}
+
+// Blank lines give `None`. Padding lines with a count of 0 parse normally and are skipped by the caller.
+fn parse_line(line: &str, prefix: &str, path: &Path, line_number: usize) -> Result<Option<(PasswordHash, u64)>, BreachError> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let malformed = || BreachError::Malformed(format!("{} line {}: expected HASH:COUNT", path.display(), line_number));
+    let (hash, count) = line.split_once(':').ok_or_else(malformed)?;
+    let hash = parse_hash(prefix, hash).ok_or_else(malformed)?;
+    let count = count.parse().map_err(|_| malformed())?;
+    Ok(Some((hash, count)))
+}
+
+fn open_reader(path: &Path) -> Result<BufReader<File>, BreachError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|_| BreachError::NotFound(path.display().to_string()))
+}
+
+impl BreachList {
+    /// Opens a combined hash file, or a directory of range files
+    pub fn open(path: &Path) -> Result<Self, BreachError> {
+        let metadata = std::fs::metadata(path)
+            .map_err(|_| BreachError::NotFound(path.display().to_string()))?;
+        let layout = if metadata.is_dir() { Layout::Ranges } else { Layout::Combined };
+
+        Ok(Self { path: path.to_path_buf(), layout })
+    }
+
+    /// How many times `password` appears in the list, 0 if it doesn't
+    pub fn occurrences(&self, password: &str) -> Result<u64, BreachError> {
+        let hash = hash_password(password);
+        Ok(self.lookup(&[hash])?.get(&hash).copied().unwrap_or(0))
+    }
+
+    /// Looks up every hash in a single pass over the list and returns the ones found with their counts
+    pub fn lookup(&self, hashes: &[PasswordHash]) -> Result<HashMap<PasswordHash, u64>, BreachError> {
+        let mut wanted = hashes.to_vec();
+        wanted.sort_unstable();
+        wanted.dedup();
+
+        match self.layout {
+            Layout::Combined => self.lookup_combined(&wanted),
+            Layout::Ranges => self.lookup_ranges(&wanted),
+        }
+    }
+
+    // Walks the file and the sorted hashes side by side, stopping once every hash has been passed
+    fn lookup_combined(&self, wanted: &[PasswordHash]) -> Result<HashMap<PasswordHash, u64>, BreachError> {
+        let mut reader = open_reader(&self.path)?;
+        let mut found = HashMap::new();
+        let mut next = wanted.iter().peekable();
+        let mut previous: Option<PasswordHash> = None;
+        let mut line = String::new();
+        let mut line_number = 0;
+
+        while next.peek().is_some() {
+            line.clear();
+            let read = reader.read_line(&mut line)
+                .map_err(|e| BreachError::ReadFailed(e.to_string()))?;
+            if read == 0 {
+                break;
+            }
+            line_number += 1;
+
+            let Some((hash, count)) = parse_line(&line, "", &self.path, line_number)? else { continue };
+            // A file in any other order would silently miss matches
+            if previous.is_some_and(|previous| hash < previous) {
+                return Err(BreachError::Malformed(format!(
+                    "{} line {}: hashes are not sorted", self.path.display(), line_number
+                )));
+            }
+            previous = Some(hash);
+
+            while next.next_if(|wanted| **wanted < hash).is_some() {}
+            if next.next_if(|wanted| **wanted == hash).is_some() && count > 0 {
+                found.insert(hash, count);
+            }
+        }
+
+        Ok(found)
+    }
+
+    // Reads only the range files of the wanted prefixes
+    fn lookup_ranges(&self, wanted: &[PasswordHash]) -> Result<HashMap<PasswordHash, u64>, BreachError> {
+        let mut by_prefix: BTreeMap<String, Vec<PasswordHash>> = BTreeMap::new();
+        for hash in wanted {
+            let hex: String = hash.iter().map(|byte| format!("{:02X}", byte)).collect();
+            by_prefix.entry(hex[..RANGE_PREFIX_LEN].to_string()).or_default().push(*hash);
+        }
+
+        let mut found = HashMap::new();
+        for (prefix, hashes) in by_prefix {
+            // A missing range file means an incomplete download, not a clean result
+            let path = self.path.join(format!("{}.txt", prefix));
+            let reader = open_reader(&path)?;
+
+            for (index, line) in reader.lines().enumerate() {
+                let line = line.map_err(|e| BreachError::ReadFailed(e.to_string()))?;
+                let Some((hash, count)) = parse_line(&line, &prefix, &path, index + 1)? else { continue };
+                if count > 0 && hashes.contains(&hash) {
+                    found.insert(hash, count);
+                }
+            }
+        }
+
+        Ok(found)
+    }
+}
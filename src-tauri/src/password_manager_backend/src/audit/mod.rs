@@ -0,0 +1,284 @@
+pub mod breach;
+pub mod strength;
+
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+use zeroize::Zeroize;
+use breach::{BreachList, PasswordHash};
+use crate::crypto::{constant_time_eq, derive_subkey, KeyHierarchy, SubkeyPurpose};
+use crate::entry::collection::EntryCollection;
+use crate::entry::Entry;
+use crate::error::app_error::AppError;
+use crate::error::crypto_error::CryptoError;
+use crate::error::vault_error::VaultError;
+
+// Audit defaults
+pub const DEFAULT_MAX_PASSWORD_AGE_DAYS: u32 = 365;
+pub const MAX_PASSWORD_AGE_DAYS_LIMIT: u32 = 10 * 365;
+pub const DEFAULT_MIN_PASSWORD_SCORE: u8 = 3; // Scores below this are reported as weak
+pub const MAX_PASSWORD_SCORE: u8 = 4;
+pub const DEFAULT_MIN_MASTER_PASSWORD_SCORE: u8 = 3;
+pub const LOWEST_MIN_MASTER_PASSWORD_SCORE: u8 = 2; // The master password policy can't be relaxed below this
+
+/// Thresholds for the security audit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditOptions {
+    // Passwords not changed for longer than this are reported as old
+    pub max_password_age_days: u32,
+    pub min_password_score: u8,
+}
+
+impl Default for AuditOptions {
+    fn default() -> Self {
+        Self {
+            max_password_age_days: DEFAULT_MAX_PASSWORD_AGE_DAYS,
+            min_password_score: DEFAULT_MIN_PASSWORD_SCORE,
+        }
+    }
+}
+
+impl AuditOptions {
+    pub fn validate(&self) -> Result<(), VaultError> {
+        if self.max_password_age_days == 0 || self.max_password_age_days > MAX_PASSWORD_AGE_DAYS_LIMIT {
+            return Err(VaultError::ValidationError(
+                format!("Password age must be between 1 and {} days", MAX_PASSWORD_AGE_DAYS_LIMIT)
+            ));
+        }
+        if self.min_password_score > MAX_PASSWORD_SCORE {
+            return Err(VaultError::ValidationError(
+                format!("Minimum password score cannot exceed {}", MAX_PASSWORD_SCORE)
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A problem found with an entry's password
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum AuditFinding {
+    Weak { score: u8, warning: Option<String> },
+    // Entries sharing a password get the same group number, counted from 1
+    Reused { group: usize, count: usize },
+    Old { age_days: i64 },
+    // Found in the breached password list this many times
+    Compromised { occurrences: u64 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryAudit {
+    pub entry_id: Uuid,
+    pub title: String,
+    pub username: Option<String>,
+    pub score: u8,
+    // When the password or another secret last changed
+    pub password_changed_at: DateTime<Utc>,
+    pub findings: Vec<AuditFinding>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditReport {
+    pub generated_at: DateTime<Utc>,
+    // Share of audited passwords without findings, 0-100; a vault without passwords scores 100
+    pub vault_score: u8,
+    pub audited_count: usize,
+    pub weak_count: usize,
+    pub reused_count: usize,
+    pub old_count: usize,
+    // Only counted when a breached password list was checked
+    pub compromised_count: Option<usize>,
+    // Every entry with a password, those with the most findings first
+    pub entries: Vec<EntryAudit>,
+}
+
+// The password last changed when the newest history version holding a different password was
+// replaced. Edits to notes, TOTP or details add history too, so versions are compared, not just dated.
+fn password_changed_at(entry: &Entry, password: &str, key_hierarchy: &KeyHierarchy) -> Result<DateTime<Utc>, AppError> {
+    for record in entry.history.iter().rev() {
+        let previous = record.sensitive_data.decrypt_password(entry.id(), key_hierarchy)?;
+        let same = previous.is_some_and(|previous| constant_time_eq(previous.as_ref().as_bytes(), password.as_bytes()));
+        if !same {
+            return Ok(record.replaced_at);
+        }
+    }
+    Ok(entry.created_at)
+}
+
+/// Checks every password in the unlocked collection for strength, reuse and age, and against
+/// `breach_list` when one is given.
+///
+/// Passwords are decrypted one at a time and dropped once scored. Reuse is found by comparing
+/// HMACs under a subkey of the MEK, so equal passwords can be grouped without keeping any
+/// plaintext around; neither the hashes nor the report are ever stored. The breach list is keyed
+/// by plain SHA-1, so those hashes are kept only until the lookup is done.
+pub fn run_audit(
+    collection: &EntryCollection,
+    key_hierarchy: &KeyHierarchy,
+    options: &AuditOptions,
+    breach_list: Option<&BreachList>,
+) -> Result<AuditReport, AppError> {
+    options.validate()?;
+
+    let hash_key = derive_subkey(key_hierarchy.mek.as_ref(), SubkeyPurpose::AuditHash)?;
+    let now = Utc::now();
+
+    let mut audits = Vec::new();
+    let mut hashes: HashMap<[u8; 32], Vec<usize>> = HashMap::new();
+    // Parallel to `audits`
+    let mut breach_hashes: Vec<PasswordHash> = Vec::new();
+    for entry in collection.entries.values() {
+        let Some(password) = entry.sensitive_data.decrypt_password(entry.id(), key_hierarchy)? else {
+            continue;
+        };
+        if password.as_ref().is_empty() {
+            continue;
+        }
+
+        let strength = strength::estimate(password.as_ref());
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(hash_key.as_ref())
+            .map_err(|_| CryptoError::InvalidKey)?;
+        mac.update(password.as_ref().as_bytes());
+        if breach_list.is_some() {
+            breach_hashes.push(breach::hash_password(password.as_ref()));
+        }
+        let changed_at = password_changed_at(entry, password.as_ref(), key_hierarchy)?;
+        drop(password);
+        hashes.entry(mac.finalize().into_bytes().into()).or_default().push(audits.len());
+
+        let mut findings = Vec::new();
+        if strength.score < options.min_password_score {
+            findings.push(AuditFinding::Weak { score: strength.score, warning: strength.warning });
+        }
+        let age_days = (now - changed_at).num_days();
+        if age_days > i64::from(options.max_password_age_days) {
+            findings.push(AuditFinding::Old { age_days });
+        }
+
+        audits.push(EntryAudit {
+            entry_id: *entry.id(),
+            title: entry.title().to_string(),
+            username: entry.username().map(str::to_string),
+            score: strength.score,
+            password_changed_at: changed_at,
+            findings,
+        });
+    }
+
+    // Number the groups in a stable order so the same vault gives the same report
+    let mut groups: Vec<Vec<usize>> = hashes.into_values().filter(|indices| indices.len() > 1).collect();
+    for indices in &mut groups {
+        indices.sort_by_key(|index| audits[*index].title.to_lowercase());
+    }
+    groups.sort_by_key(|indices| audits[indices[0]].title.to_lowercase());
+    for (number, indices) in groups.iter().enumerate() {
+        for index in indices {
+            audits[*index].findings.push(AuditFinding::Reused { group: number + 1, count: indices.len() });
+        }
+    }
+
+    if let Some(list) = breach_list {
+        let found = list.lookup(&breach_hashes);
+        if let Ok(found) = &found {
+            for (audit, hash) in audits.iter_mut().zip(&breach_hashes) {
+                if let Some(occurrences) = found.get(hash) {
+                    audit.findings.push(AuditFinding::Compromised { occurrences: *occurrences });
+                }
+            }
+        }
+        breach_hashes.zeroize();
+        found?;
+    }
+
+    let count = |matches: fn(&AuditFinding) -> bool| {
+        audits.iter().filter(|audit| audit.findings.iter().any(matches)).count()
+    };
+    let weak_count = count(|finding| matches!(finding, AuditFinding::Weak { .. }));
+    let reused_count = count(|finding| matches!(finding, AuditFinding::Reused { .. }));
+    let old_count = count(|finding| matches!(finding, AuditFinding::Old { .. }));
+    let compromised_count = breach_list
+        .map(|_| count(|finding| matches!(finding, AuditFinding::Compromised { .. })));
+
+    let clean = audits.iter().filter(|audit| audit.findings.is_empty()).count();
+    let vault_score = match audits.len() {
+        0 => 100,
+        audited => (clean * 100 / audited) as u8,
+    };
+
+    audits.sort_by_key(|audit| (std::cmp::Reverse(audit.findings.len()), audit.title.to_lowercase()));
+    Ok(AuditReport {
+        generated_at: now,
+        vault_score,
+        audited_count: audits.len(),
+        weak_count,
+        reused_count,
+        old_count,
+        compromised_count,
+        entries: audits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use crate::entry::kind::EntryKind;
+    use crate::entry::overview::EntryData;
+
+    fn entry_data(notes: &str) -> EntryData {
+        EntryData {
+            title: "Bank".to_string(),
+            username: None,
+            password: Some("correct horse battery staple".to_string()),
+            url: None,
+            notes: Some(notes.to_string()),
+            category_name: None,
+            favorite: false,
+            totp: None,
+            custom_fields: Vec::new(),
+            kind: EntryKind::default(),
+            details: None,
+        }
+    }
+
+    fn is_old(report: &AuditReport) -> bool {
+        report.entries[0].findings.iter().any(|finding| matches!(finding, AuditFinding::Old { .. }))
+    }
+
+    #[test]
+    fn editing_only_the_notes_keeps_an_old_password_old() {
+        let (key_hierarchy, _) = KeyHierarchy::new(b"master password").unwrap();
+        let mut collection = EntryCollection::new();
+        let id = collection.create_entry(entry_data("first"), &key_hierarchy).unwrap();
+        let created_at = Utc::now() - Duration::days(i64::from(DEFAULT_MAX_PASSWORD_AGE_DAYS) + 30);
+        collection.entries.get_mut(&id).unwrap().created_at = created_at;
+
+        collection.update_entry(&id, entry_data("second"), &key_hierarchy).unwrap();
+        assert_eq!(collection.entries[&id].history.len(), 1);
+
+        let report = run_audit(&collection, &key_hierarchy, &AuditOptions::default(), None).unwrap();
+        assert!(is_old(&report));
+        assert_eq!(report.entries[0].password_changed_at, created_at);
+    }
+
+    #[test]
+    fn changing_the_password_resets_its_age() {
+        let (key_hierarchy, _) = KeyHierarchy::new(b"master password").unwrap();
+        let mut collection = EntryCollection::new();
+        let id = collection.create_entry(entry_data("notes"), &key_hierarchy).unwrap();
+        collection.entries.get_mut(&id).unwrap().created_at = Utc::now() - Duration::days(1000);
+
+        let mut changed = entry_data("notes");
+        changed.password = Some("a different long passphrase".to_string());
+        collection.update_entry(&id, changed, &key_hierarchy).unwrap();
+
+        let report = run_audit(&collection, &key_hierarchy, &AuditOptions::default(), None).unwrap();
+        assert!(!is_old(&report));
+    }
+}
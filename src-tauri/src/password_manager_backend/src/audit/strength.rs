@@ -0,0 +1,85 @@
+// password_manager_backend/src/audit/strength.rs
+
+use serde::{Deserialize, Serialize};
+use crate::audit::{DEFAULT_MIN_MASTER_PASSWORD_SCORE, LOWEST_MIN_MASTER_PASSWORD_SCORE, MAX_PASSWORD_SCORE};
+use crate::error::app_error::AppError;
+use crate::error::vault_error::VaultError;
+
+// Words an attacker targeting this vault would try first
+const APP_WORDS: [&str; 2] = ["pawpass", "paw pass"];
+
+/// Estimated strength of a single password
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordStrength {
+    // 0 (trivial) to 4 (strong)
+    pub score: u8,
+    // Guesses an attacker trying common passwords, words, dates and patterns first would need
+    pub guesses: u64,
+    pub guesses_log10: f64,
+    // Why the password is weak, if it is
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+/// Estimates strength the way an attacker would guess: the password is split into dictionary
+/// words (including l33t and reversed spellings), keyboard walks, dates, repeats and sequences,
+/// and scored by the cheapest combination that covers it. Long passphrases of plain words score
+/// well; `Password1!` doesn't.
+pub fn estimate(password: &str) -> PasswordStrength {
+    let entropy = zxcvbn::zxcvbn(password, &APP_WORDS);
+    let (warning, suggestions) = match entropy.feedback() {
+        Some(feedback) => (
+            feedback.warning().map(|warning| warning.to_string()),
+            feedback.suggestions().iter().map(ToString::to_string).collect(),
+        ),
+        None => (None, Vec::new()),
+    };
+
+    PasswordStrength {
+        score: entropy.score().into(),
+        guesses: entropy.guesses(),
+        guesses_log10: entropy.guesses_log10(),
+        warning,
+        suggestions,
+    }
+}
+
+/// How strong the master password, and passwords protecting exports, must be
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordPolicy {
+    pub min_score: u8,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self { min_score: DEFAULT_MIN_MASTER_PASSWORD_SCORE }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn validate(&self) -> Result<(), VaultError> {
+        if !(LOWEST_MIN_MASTER_PASSWORD_SCORE..=MAX_PASSWORD_SCORE).contains(&self.min_score) {
+            return Err(VaultError::ValidationError(format!(
+                "Minimum password score must be between {} and {}",
+                LOWEST_MIN_MASTER_PASSWORD_SCORE, MAX_PASSWORD_SCORE
+            )));
+        }
+        Ok(())
+    }
+
+    /// Fails with the estimator's feedback when `password` scores below the minimum
+    pub fn check(&self, password: &str) -> Result<PasswordStrength, AppError> {
+        let strength = estimate(password);
+        if strength.score < self.min_score {
+            return Err(AppError::PasswordTooWeak {
+                score: strength.score,
+                min_score: self.min_score,
+                warning: strength.warning,
+                suggestions: strength.suggestions,
+            });
+        }
+        Ok(strength)
+    }
+}
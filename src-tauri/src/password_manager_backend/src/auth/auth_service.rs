@@ -1,19 +1,35 @@
 // password_manager_backend/src/auth/auth_service.rs
 
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use zeroize::Zeroize;
+use crate::_vault::CURRENT_FORMAT_VERSION;
 use crate::crypto::{
     key_derivation::KeyDerivation,
     key_hierarchy::KeyHierarchy,
-    secure::SecureMemory,
+    secure::{SecureMemory, constant_time_eq},
     EncryptedData,
 };
 use crate::error::auth_error::AuthError;
 
+/// How the stored verifier and the MEK wrap relate to the master key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyScheme {
+    /// The verifier is the raw Argon2 output and the MEK is wrapped with that same value.
+    /// Only found in vaults created before the key split; upgraded on the next login.
+    Legacy,
+    /// The verifier and the MEK-wrapping key are independent HKDF subkeys of the master key
+    Split,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthService {
-    // Store password hash for quick validation without full key derivation
-    master_password_hash: Vec<u8>,
+    // Verifier split from the master key for quick validation;
+    // it cannot be used to unwrap the MEK
+    verifier: Vec<u8>,
+
+    // Which derivation produced `verifier` and `encrypted_mek`
+    key_scheme: KeyScheme,
 
     // Salt used for password hashing
     salt: Vec<u8>,
@@ -21,6 +37,12 @@ pub struct AuthService {
     // Encrypted Master Encryption Key (MEK)
     encrypted_mek: EncryptedData,
 
+    // Random id of this vault; bound into the MEK wrap and the sealed entries
+    vault_id: Uuid,
+
+    // Format version the MEK wrap is bound to, `None` for wraps made without associated data
+    mek_wrap_version: Option<u16>,
+
     // Key derivation settings
     key_derivation: KeyDerivation,
 }
@@ -37,17 +59,24 @@ impl AuthService {
         let (key_hierarchy, salt) = KeyHierarchy::new(password_bytes)
             .map_err(|e| AuthError::from(e))?;
 
-        let master_password_hash = unsafe {
-            key_hierarchy.key_derivation.derive_key(password_bytes, salt.as_slice())?.into_inner()
+        let verifier = unsafe {
+            key_hierarchy.verifier()?.into_inner()
         };
 
-        let encrypted_mek = key_hierarchy.encrypted_mek(&master_password_hash)?;
+        let vault_id = Uuid::new_v4();
+        let encrypted_mek = key_hierarchy.encrypted_mek(
+            key_hierarchy.master_key.as_ref(),
+            &Self::mek_aad_for(&vault_id, CURRENT_FORMAT_VERSION)
+        )?;
 
         let auth_service = AuthService {
-            master_password_hash,
+            verifier,
+            key_scheme: KeyScheme::Split,
             salt,
             encrypted_mek,
-            key_derivation: KeyDerivation::default(),
+            vault_id,
+            mek_wrap_version: Some(CURRENT_FORMAT_VERSION),
+            key_derivation: key_hierarchy.key_derivation.clone(),
         };
 
         println!("Created new AuthService");
@@ -56,29 +85,43 @@ impl AuthService {
     }
 
     /// Unlocks the _vault with the provided master password
-    /// This is what we call after loading the AuthService from disk
+    /// This is what we call after loading the AuthService from disk.
+    /// Legacy vaults are upgraded to the split key scheme, and MEK wraps from older formats are re-bound,
+    /// so the caller should save afterwards.
     pub fn unlock(
         &mut self,
         master_password: SecureMemory<String>
     ) -> Result<KeyHierarchy, AuthError> {
         let input_bytes = master_password.as_ref().as_bytes();
-        let mut input_hash = unsafe {
-            self.key_derivation.derive_key(input_bytes, self.salt.as_slice())?.into_inner()
-        };
+        let master_key = self.key_derivation.derive_key(input_bytes, self.salt.as_slice())?;
 
-        if !input_hash.eq(&self.master_password_hash) {
+        if !self.matches_verifier(master_key.as_ref())? {
             return Err(AuthError::InvalidPassword);
         }
 
-        // Use the new method with current settings
-        let key_hierarchy = KeyHierarchy::from_existing(
-            input_bytes, 
-            &self.encrypted_mek, 
-            &self.salt,
-            self.key_derivation.clone()
-        )?;
-
-        input_hash.zeroize();
+        let key_hierarchy = match self.key_scheme {
+            KeyScheme::Split => {
+                let key_hierarchy = KeyHierarchy::from_master_key(
+                    master_key,
+                    &self.encrypted_mek,
+                    self.key_derivation.clone(),
+                    &self.mek_aad()
+                )?;
+                if self.mek_wrap_version != Some(CURRENT_FORMAT_VERSION) {
+                    self.rewrap_mek(&key_hierarchy)?;
+                }
+                key_hierarchy
+            }
+            KeyScheme::Legacy => {
+                let key_hierarchy = KeyHierarchy::from_legacy_master_key(
+                    master_key,
+                    &self.encrypted_mek,
+                    self.key_derivation.clone()
+                )?;
+                self.upgrade_key_scheme(&key_hierarchy)?;
+                key_hierarchy
+            }
+        };
 
         Ok(key_hierarchy)
     }
@@ -93,20 +136,12 @@ impl AuthService {
             old_password.into_inner()
         };
     
-        // First verify the old password
-        self.verify_master_password(SecureMemory::new(old_password.clone()))?;
+        // First verify the old password and open the current key hierarchy
+        let mut key_hierarchy = self.unlock(SecureMemory::new(old_password.clone()))?;
     
         let new_password_bytes = new_password.as_ref().as_bytes();
         let old_password_bytes = old_password.as_bytes();
     
-        // Use current settings when creating new key hierarchy
-        let mut key_hierarchy = KeyHierarchy::from_existing(
-            old_password_bytes, 
-            &self.encrypted_mek, 
-            &self.salt,
-            self.key_derivation.clone()  // Pass current settings
-        )?;
-    
         // Update salt
         self.salt = key_hierarchy.change_master_password(
             old_password_bytes, 
@@ -114,16 +149,9 @@ impl AuthService {
             &self.salt
         )?;
     
-        // Update master password hash using current settings
-        unsafe {
-            self.master_password_hash = self.key_derivation.derive_key(
-                new_password_bytes, 
-                &self.salt
-            )?.into_inner();
-        }
-    
-        // Update encrypted mek
-        self.encrypted_mek = key_hierarchy.encrypted_mek(&self.master_password_hash)?;
+        // Split a fresh verifier and MEK wrap from the new master key
+        self.replace_verifier(&key_hierarchy)?;
+        self.rewrap_mek(&key_hierarchy)?;
     
         old_password.zeroize();
 
@@ -143,29 +171,22 @@ impl AuthService {
             self.key_derivation.parallelism()
         );
 
-        let mut key_hierarchy = KeyHierarchy::from_existing(
-            master_password.as_ref().as_bytes(),
-            &self.encrypted_mek,
-            &self.salt,
-            self.key_derivation.clone()
-        )?;
+        let mut key_hierarchy = self.unlock(SecureMemory::new(master_password.as_ref().clone()))?;
 
         let (new_salt, encrypted_mek) = key_hierarchy.update_key_derivation(
             master_password.as_ref().as_bytes(),
             &self.salt,
-            new_key_derivation.clone()
+            new_key_derivation.clone(),
+            &Self::mek_aad_for(&self.vault_id, CURRENT_FORMAT_VERSION)
         )?;
 
-        println!("🔒 Updating master password hash with new settings");
-        unsafe {
-            self.master_password_hash = new_key_derivation
-                .derive_key(master_password.as_ref().as_bytes(), &new_salt)?
-                .into_inner();
-        }
+        println!("🔒 Updating verifier with new settings");
+        self.replace_verifier(&key_hierarchy)?;
 
         println!("📝 Updating AuthService state with new values");
         self.key_derivation = new_key_derivation;
         self.encrypted_mek = encrypted_mek;
+        self.mek_wrap_version = Some(CURRENT_FORMAT_VERSION);
         self.salt = new_salt;
 
         println!("✅ AuthService key derivation update complete");
@@ -179,20 +200,84 @@ impl AuthService {
     ) -> Result<bool, AuthError> {
         // 1. Hash password with stored salt
         let password_bytes = password.as_ref().as_bytes();
-        let derived_hash = self
+        let derived_key = self
             .key_derivation
             .derive_key(password_bytes, &self.salt)?;
 
-        // 2. Compare with stored hash
-        let result = derived_hash.as_ref().eq(&self.master_password_hash);
+        // 2. Compare with stored verifier
+        let result = self.matches_verifier(derived_key.as_ref())?;
 
         drop(password);
-        drop(derived_hash);
+        drop(derived_key);
 
         
         Ok(result)
     }
 
+    /// Whether an already derived master key (e.g. from quick unlock) belongs to this vault
+    pub fn verify_master_key(&self, master_key: &[u8]) -> Result<bool, AuthError> {
+        self.matches_verifier(master_key)
+    }
+
+    // Checks a freshly derived master key against the stored verifier
+    fn matches_verifier(&self, master_key: &[u8]) -> Result<bool, AuthError> {
+        let result = match self.key_scheme {
+            KeyScheme::Legacy => constant_time_eq(master_key, &self.verifier),
+            KeyScheme::Split => {
+                let expected = KeyHierarchy::verifier_for_key(master_key)?;
+                constant_time_eq(expected.as_ref(), &self.verifier)
+            }
+        };
+
+        Ok(result)
+    }
+
+    fn replace_verifier(&mut self, key_hierarchy: &KeyHierarchy) -> Result<(), AuthError> {
+        let verifier = unsafe {
+            key_hierarchy.verifier()?.into_inner()
+        };
+        self.verifier.zeroize();
+        self.verifier = verifier;
+        Ok(())
+    }
+
+    // Associated data for the MEK wrap: the vault id plus the format version it was wrapped under,
+    // so a wrap copied from another vault (or replayed from another format) fails to open
+    fn mek_aad_for(vault_id: &Uuid, format_version: u16) -> Vec<u8> {
+        let mut aad = b"pawpass/mek/".to_vec();
+        aad.extend_from_slice(vault_id.as_bytes());
+        aad.extend_from_slice(&format_version.to_le_bytes());
+        aad
+    }
+
+    fn mek_aad(&self) -> Vec<u8> {
+        match self.mek_wrap_version {
+            Some(version) => Self::mek_aad_for(&self.vault_id, version),
+            None => Vec::new(),
+        }
+    }
+
+    // Wraps the MEK under the split wrapping key, bound to the current format version
+    fn rewrap_mek(&mut self, key_hierarchy: &KeyHierarchy) -> Result<(), AuthError> {
+        println!("Re-wrapping MEK for format v{}", CURRENT_FORMAT_VERSION);
+        self.encrypted_mek = key_hierarchy.encrypted_mek(
+            key_hierarchy.master_key.as_ref(),
+            &Self::mek_aad_for(&self.vault_id, CURRENT_FORMAT_VERSION)
+        )?;
+        self.mek_wrap_version = Some(CURRENT_FORMAT_VERSION);
+        Ok(())
+    }
+
+    // Re-wraps the MEK with the split wrapping key and replaces the raw Argon2 output
+    // that legacy vaults stored as their password hash
+    fn upgrade_key_scheme(&mut self, key_hierarchy: &KeyHierarchy) -> Result<(), AuthError> {
+        println!("Upgrading vault to split key scheme");
+        self.rewrap_mek(key_hierarchy)?;
+        self.replace_verifier(key_hierarchy)?;
+        self.key_scheme = KeyScheme::Split;
+        Ok(())
+    }
+
     // Getters
     pub fn key_derivation(&self) -> &KeyDerivation {
         &self.key_derivation
@@ -203,9 +288,21 @@ impl AuthService {
         &self.encrypted_mek
     }
 
-    // Expose master password hash for storage
-    pub fn master_password_hash(&self) -> &[u8] {
-        &self.master_password_hash
+    // Expose password verifier for storage
+    pub fn verifier(&self) -> &[u8] {
+        &self.verifier
+    }
+
+    pub fn key_scheme(&self) -> KeyScheme {
+        self.key_scheme
+    }
+
+    pub fn vault_id(&self) -> &Uuid {
+        &self.vault_id
+    }
+
+    pub fn mek_wrap_version(&self) -> Option<u16> {
+        self.mek_wrap_version
     }
 
     // Expose salt for storage
@@ -217,7 +314,7 @@ impl AuthService {
 
 impl Zeroize for AuthService {
     fn zeroize(&mut self) {
-        self.master_password_hash.zeroize();
+        self.verifier.zeroize();
         self.salt.zeroize();
         self.encrypted_mek.zeroize();
     }
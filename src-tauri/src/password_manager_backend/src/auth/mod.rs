@@ -1 +1,15 @@
-pub mod auth_service;
\ No newline at end of file
+pub mod auth_service;
+pub mod unlock_throttle;
+pub mod quick_unlock;
+
+// Unlock Rate Limiting Constants
+pub const FREE_UNLOCK_ATTEMPTS: u32 = 3;        // Failures allowed before any delay kicks in
+pub const BASE_BACKOFF_SECONDS: u64 = 2;        // Delay after the first throttled failure, doubled each time
+pub const MAX_BACKOFF_SECONDS: u64 = 15 * 60;   // Never make the user wait longer than 15 minutes
+pub const MIN_WIPE_THRESHOLD: u32 = 5;          // Lowest allowed wipe-after-N setting
+pub const MAX_WIPE_THRESHOLD: u32 = 100;
+
+// Quick Unlock Constants
+pub const MIN_PIN_LENGTH: usize = 4;
+pub const MAX_PIN_LENGTH: usize = 12;
+pub const MAX_PIN_ATTEMPTS: u32 = 3;            // Wrong PINs allowed before the master password is required again
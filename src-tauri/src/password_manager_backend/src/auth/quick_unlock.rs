@@ -0,0 +1,118 @@
+// password_manager_backend/src/auth/quick_unlock.rs
+
+use serde::Serialize;
+use zeroize::Zeroize;
+use crate::auth::{MAX_PIN_ATTEMPTS, MAX_PIN_LENGTH, MIN_PIN_LENGTH};
+use crate::crypto::{EncryptedData, KeyDerivation, KeyHierarchy, SecureMemory};
+use crate::error::auth_error::AuthError;
+
+/// Opt-in PIN unlock for a vault that was already opened with the master password.
+///
+/// The master key and MEK are wrapped under an Argon2 key derived from the PIN and only ever
+/// held in memory, so quitting the app always falls back to the master password.
+/// A PIN has little entropy; the small attempt budget is what keeps it safe to use.
+pub struct QuickUnlock {
+    salt: Vec<u8>,
+    pin_key_derivation: KeyDerivation,
+    wrapped_keys: EncryptedData,
+    // Settings of the vault's own key hierarchy, restored on unlock
+    vault_key_derivation: KeyDerivation,
+    failed_attempts: u32,
+}
+
+/// Snapshot for the lock screen
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickUnlockStatus {
+    pub enabled: bool,
+    #[serde(rename = "remainingAttempts")]
+    pub remaining_attempts: u32,
+}
+
+impl QuickUnlock {
+    /// Wraps the keys of an unlocked vault under `pin`
+    pub fn enable(pin: SecureMemory<String>, key_hierarchy: &KeyHierarchy) -> Result<Self, AuthError> {
+        Self::validate_pin(pin.as_ref())?;
+
+        let pin_key_derivation = KeyDerivation::default();
+        let salt = pin_key_derivation.generate_salt()?;
+        let pin_key = pin_key_derivation.derive_key(pin.as_ref().as_bytes(), &salt)?;
+
+        let wrapped_keys = key_hierarchy.wrap_for_quick_unlock(pin_key.as_ref())?;
+
+        Ok(Self {
+            salt,
+            pin_key_derivation,
+            wrapped_keys,
+            vault_key_derivation: key_hierarchy.key_derivation.clone(),
+            failed_attempts: 0,
+        })
+    }
+
+    /// Rebuilds the key hierarchy from the PIN.
+    /// Once the attempts run out this returns `QuickUnlockUnavailable` and the caller should discard it.
+    pub fn unlock(&mut self, pin: SecureMemory<String>) -> Result<KeyHierarchy, AuthError> {
+        if self.is_exhausted() {
+            return Err(AuthError::QuickUnlockUnavailable);
+        }
+
+        let pin_key = self.pin_key_derivation.derive_key(pin.as_ref().as_bytes(), &self.salt)?;
+
+        match KeyHierarchy::from_quick_unlock(
+            pin_key.as_ref(),
+            &self.wrapped_keys,
+            self.vault_key_derivation.clone()
+        ) {
+            Ok(key_hierarchy) => {
+                self.failed_attempts = 0;
+                Ok(key_hierarchy)
+            }
+            Err(_) => {
+                self.failed_attempts += 1;
+                if self.is_exhausted() {
+                    return Err(AuthError::QuickUnlockUnavailable);
+                }
+                Err(AuthError::InvalidPin { remaining_attempts: self.remaining_attempts() })
+            }
+        }
+    }
+
+    pub fn remaining_attempts(&self) -> u32 {
+        MAX_PIN_ATTEMPTS.saturating_sub(self.failed_attempts)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining_attempts() == 0
+    }
+
+    pub fn status(&self) -> QuickUnlockStatus {
+        QuickUnlockStatus {
+            enabled: !self.is_exhausted(),
+            remaining_attempts: self.remaining_attempts(),
+        }
+    }
+
+    fn validate_pin(pin: &str) -> Result<(), AuthError> {
+        if pin.len() < MIN_PIN_LENGTH || pin.len() > MAX_PIN_LENGTH {
+            return Err(AuthError::InvalidPinFormat(
+                format!("PIN must be between {} and {} digits", MIN_PIN_LENGTH, MAX_PIN_LENGTH)
+            ));
+        }
+        if !pin.chars().all(|c| c.is_ascii_digit()) {
+            return Err(AuthError::InvalidPinFormat("PIN may only contain digits".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl Zeroize for QuickUnlock {
+    fn zeroize(&mut self) {
+        self.salt.zeroize();
+        self.wrapped_keys.zeroize();
+    }
+}
+
+impl Drop for QuickUnlock {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
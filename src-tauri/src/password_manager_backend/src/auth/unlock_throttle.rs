@@ -0,0 +1,141 @@
+// password_manager_backend/src/auth/unlock_throttle.rs
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use crate::auth::{
+    BASE_BACKOFF_SECONDS,
+    FREE_UNLOCK_ATTEMPTS,
+    MAX_BACKOFF_SECONDS,
+    MAX_WIPE_THRESHOLD,
+    MIN_WIPE_THRESHOLD,
+};
+use crate::error::auth_error::AuthError;
+
+/// Failed unlock attempts, persisted beside the vault so restarting the app does not reset them.
+/// After `FREE_UNLOCK_ATTEMPTS` failures every further attempt has to wait an exponentially growing delay.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UnlockThrottle {
+    #[serde(rename = "failedAttempts")]
+    failed_attempts: u32,
+    #[serde(rename = "lastFailure")]
+    last_failure: Option<DateTime<Utc>>,
+    // Copy of the vault's policy, taken whenever the vault is unlocked or the policy changes.
+    // Failed attempts happen before the vault can be authenticated, so its own copy can't be trusted then.
+    // Anyone able to rewrite this file could delete the failure count just as well.
+    #[serde(default)]
+    policy: UnlockPolicy,
+}
+
+/// User-chosen response to repeated unlock failures. The vault keeps it sealed; the throttle keeps a copy.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UnlockPolicy {
+    /// Delete the vault and its backups after this many consecutive failures (disabled when `None`)
+    #[serde(rename = "wipeAfterFailures")]
+    pub wipe_after_failures: Option<u32>,
+}
+
+/// Snapshot of the throttle for the login screen
+#[derive(Debug, Clone, Serialize)]
+pub struct UnlockThrottleStatus {
+    #[serde(rename = "failedAttempts")]
+    pub failed_attempts: u32,
+    #[serde(rename = "retryAfterSeconds")]
+    pub retry_after_seconds: u64,
+    #[serde(rename = "wipeAfterFailures")]
+    pub wipe_after_failures: Option<u32>,
+}
+
+impl UnlockThrottle {
+    /// Returns an error if the caller must wait before trying again
+    pub fn check(&self, now: DateTime<Utc>) -> Result<(), AuthError> {
+        let retry_after_seconds = self.retry_after_seconds(now);
+        if retry_after_seconds > 0 {
+            return Err(AuthError::TooManyAttempts { retry_after_seconds });
+        }
+        Ok(())
+    }
+
+    /// Seconds left until the next attempt is allowed (0 when no delay applies)
+    pub fn retry_after_seconds(&self, now: DateTime<Utc>) -> u64 {
+        let Some(last_failure) = self.last_failure else {
+            return 0;
+        };
+
+        // A clock that moved backwards should not lock the user out forever
+        if now < last_failure {
+            return self.backoff().num_seconds().max(0) as u64;
+        }
+
+        let allowed_at = last_failure + self.backoff();
+        (allowed_at - now).num_seconds().max(0) as u64
+    }
+
+    /// Delay required after the current number of failures
+    pub fn backoff(&self) -> Duration {
+        if self.failed_attempts < FREE_UNLOCK_ATTEMPTS {
+            return Duration::zero();
+        }
+
+        let exponent = (self.failed_attempts - FREE_UNLOCK_ATTEMPTS).min(32);
+        let seconds = BASE_BACKOFF_SECONDS
+            .saturating_mul(1u64 << exponent)
+            .min(MAX_BACKOFF_SECONDS);
+
+        Duration::seconds(seconds as i64)
+    }
+
+    pub fn record_failure(&mut self, now: DateTime<Utc>) {
+        self.failed_attempts = self.failed_attempts.saturating_add(1);
+        self.last_failure = Some(now);
+    }
+
+    pub fn reset(&mut self) {
+        self.failed_attempts = 0;
+        self.last_failure = None;
+    }
+
+    pub fn failed_attempts(&self) -> u32 {
+        self.failed_attempts
+    }
+
+    pub fn policy(&self) -> &UnlockPolicy {
+        &self.policy
+    }
+
+    /// Replaces the copy of the vault's policy; returns whether it changed and needs saving
+    pub fn set_policy(&mut self, policy: &UnlockPolicy) -> bool {
+        if self.policy == *policy {
+            return false;
+        }
+        self.policy = policy.clone();
+        true
+    }
+
+    /// Whether the vault should be destroyed after the latest failure
+    pub fn should_wipe(&self) -> bool {
+        self.policy.wipe_after_failures
+            .is_some_and(|threshold| self.failed_attempts >= threshold)
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> UnlockThrottleStatus {
+        UnlockThrottleStatus {
+            failed_attempts: self.failed_attempts,
+            retry_after_seconds: self.retry_after_seconds(now),
+            wipe_after_failures: self.policy.wipe_after_failures,
+        }
+    }
+}
+
+impl UnlockPolicy {
+    pub fn validate(&self) -> Result<(), AuthError> {
+        if let Some(threshold) = self.wipe_after_failures {
+            if !(MIN_WIPE_THRESHOLD..=MAX_WIPE_THRESHOLD).contains(&threshold) {
+                return Err(AuthError::InvalidUnlockPolicy(format!(
+                    "Wipe threshold must be between {} and {} attempts",
+                    MIN_WIPE_THRESHOLD, MAX_WIPE_THRESHOLD
+                )));
+            }
+        }
+        Ok(())
+    }
+}
@@ -7,6 +7,9 @@ pub struct EncryptedData {
     ciphertext: Vec<u8>,
     nonce: [u8; 12],     // 96 bits for AES-GCM
     tag: [u8; 16],       // 128 bits authentication tag
+    // False for blobs written before associated data was bound into the tag
+    #[serde(default)]
+    aad_bound: bool,
 }
 
 impl EncryptedData {
@@ -16,6 +19,7 @@ impl EncryptedData {
             ciphertext,
             nonce,
             tag,
+            aad_bound: true,
         };
         encrypted_data.validate()?;
         Ok(encrypted_data)
@@ -45,6 +49,12 @@ impl EncryptedData {
         &self.tag
     }
 
+    /// Whether the tag also authenticates associated data (e.g. the owning entry and field).
+    /// Unbound blobs are accepted for migration and re-encrypted on the next login.
+    pub fn is_aad_bound(&self) -> bool {
+        self.aad_bound
+    }
+
     /// Creates a zeroed instance (useful for testing)
     #[cfg(test)]
     pub fn zero() -> Self {
@@ -52,6 +62,7 @@ impl EncryptedData {
             ciphertext: vec![0u8; 32],
             nonce: [0u8; 12],
             tag: [0u8; 16],
+            aad_bound: true,
         }
     }
 }
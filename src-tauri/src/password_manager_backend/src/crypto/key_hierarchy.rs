@@ -1,15 +1,22 @@
 use rand::{RngCore, rngs::OsRng};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use aes_gcm::{
-    aead::{Aead, KeyInit},
+    aead::{Aead, KeyInit, Payload},
     Aes256Gcm, Nonce,
 };
 
-use crate::crypto::secure::SecureMemory;
+use zeroize::Zeroize;
+use crate::crypto::secure::{SecureMemory, constant_time_eq};
 use crate::crypto::key_derivation::KeyDerivation;
 use crate::crypto::encrypted_data::EncryptedData;
+use crate::crypto::subkey::{derive_subkey, SubkeyPurpose};
 use crate::crypto::KEY_SIZE;
 use crate::error::crypto_error::CryptoError;
 
+// Associated data for the quick-unlock wrap, so it cannot be confused with any other blob
+const QUICK_UNLOCK_AAD: &[u8] = b"pawpass/quick-unlock/v1";
+
 /// Key Hierarchy structure that contains the:
 /// ```markdown
 /// 1. Master key
@@ -51,15 +58,105 @@ impl KeyHierarchy {
         encrypted_mek: &EncryptedData,
         salt: &[u8],
         key_derivation_settings: KeyDerivation,
+        mek_aad: &[u8],
     ) -> Result<Self, CryptoError> {
         let key_derivation = key_derivation_settings;
         key_derivation.validate_salt(salt)?;
         
         let master_key = key_derivation.derive_key(master_password, salt)?;
-        
-        let mek_vec = Self::decrypt_mek_with_key(encrypted_mek, master_key.as_ref())
+
+        Self::from_master_key(master_key, encrypted_mek, key_derivation, mek_aad)
+    }
+
+    /// Builds the hierarchy from an already derived master key, avoiding a second Argon2 run.
+    /// `mek_aad` must match the associated data the MEK was wrapped with (empty for unbound wraps).
+    pub fn from_master_key(
+        master_key: SecureMemory<Vec<u8>>,
+        encrypted_mek: &EncryptedData,
+        key_derivation: KeyDerivation,
+        mek_aad: &[u8],
+    ) -> Result<Self, CryptoError> {
+        let mek_vec = Self::decrypt_mek_with_key(encrypted_mek, master_key.as_ref(), mek_aad)
+            .map_err(|_| CryptoError::MekDecryptionFailed)?;
+
+        Self::with_mek(master_key, mek_vec, key_derivation)
+    }
+
+    /// Opens a MEK that was wrapped directly with the master key (vaults created before the key split).
+    /// Only used to migrate those vaults on their next login.
+    pub fn from_legacy_master_key(
+        master_key: SecureMemory<Vec<u8>>,
+        encrypted_mek: &EncryptedData,
+        key_derivation: KeyDerivation,
+    ) -> Result<Self, CryptoError> {
+        let mek_vec = Self::unwrap_mek(encrypted_mek, master_key.as_ref(), &[])
             .map_err(|_| CryptoError::MekDecryptionFailed)?;
 
+        Self::with_mek(master_key, mek_vec, key_derivation)
+    }
+
+    /// Rebuilds the hierarchy from keys wrapped by `wrap_for_quick_unlock`, skipping the master-password Argon2 run
+    pub fn from_quick_unlock(
+        pin_key: &[u8],
+        wrapped_keys: &EncryptedData,
+        key_derivation: KeyDerivation,
+    ) -> Result<Self, CryptoError> {
+        let cipher = Aes256Gcm::new_from_slice(pin_key)
+            .map_err(|_| CryptoError::DecryptionFailed)?;
+
+        let nonce = Nonce::try_from(wrapped_keys.nonce().as_slice())
+            .map_err(|_| CryptoError::DecryptionFailed)?;
+
+        let mut ciphertext_with_tag = wrapped_keys.ciphertext().to_vec();
+        ciphertext_with_tag.extend_from_slice(wrapped_keys.tag());
+
+        let mut keys = SecureMemory::new(
+            cipher
+                .decrypt(&nonce, Payload { msg: ciphertext_with_tag.as_slice(), aad: QUICK_UNLOCK_AAD })
+                .map_err(|_| CryptoError::DecryptionFailed)?
+        );
+
+        if keys.as_ref().len() != 2 * KEY_SIZE {
+            return Err(CryptoError::InvalidKeyLength);
+        }
+        let mek_vec = keys.as_mut().split_off(KEY_SIZE);
+        let master_key = SecureMemory::new(keys.as_ref().clone());
+
+        Self::with_mek(master_key, mek_vec, key_derivation)
+    }
+
+    /// Encrypts the master key and MEK together under a PIN-derived key for quick unlock
+    pub fn wrap_for_quick_unlock(&self, pin_key: &[u8]) -> Result<EncryptedData, CryptoError> {
+        let mut keys = SecureMemory::new(Vec::with_capacity(2 * KEY_SIZE));
+        keys.as_mut().extend_from_slice(self.master_key.as_ref());
+        keys.as_mut().extend_from_slice(self.mek.as_ref());
+
+        let mut nonce_bytes = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::try_from(&nonce_bytes[..])
+            .map_err(|_| CryptoError::EncryptionFailed)?;
+
+        let cipher = Aes256Gcm::new_from_slice(pin_key)
+            .map_err(|_| CryptoError::EncryptionFailed)?;
+
+        let ciphertext = cipher
+            .encrypt(&nonce, Payload { msg: keys.as_ref().as_slice(), aad: QUICK_UNLOCK_AAD })
+            .map_err(|_| CryptoError::EncryptionFailed)?;
+
+        let tag_start = ciphertext.len() - 16;
+        let mut tag = [0u8; 16];
+        tag.copy_from_slice(&ciphertext[tag_start..]);
+
+        let ciphertext = ciphertext[..tag_start].to_vec();
+
+        EncryptedData::new(ciphertext, nonce_bytes, tag)
+    }
+
+    fn with_mek(
+        master_key: SecureMemory<Vec<u8>>,
+        mek_vec: Vec<u8>,
+        key_derivation: KeyDerivation,
+    ) -> Result<Self, CryptoError> {
         if mek_vec.len() != KEY_SIZE {
             return Err(CryptoError::InvalidKeyLength);
         }
@@ -73,6 +170,17 @@ impl KeyHierarchy {
         })
     }
 
+    /// Derives the value stored in the vault to verify the master password.
+    /// It is split from the master key with its own label, so it cannot be used to unwrap the MEK.
+    pub fn verifier(&self) -> Result<SecureMemory<Vec<u8>>, CryptoError> {
+        Self::verifier_for_key(self.master_key.as_ref())
+    }
+
+    /// Same as `verifier`, for a master key that is not (yet) part of a hierarchy
+    pub fn verifier_for_key(master_key: &[u8]) -> Result<SecureMemory<Vec<u8>>, CryptoError> {
+        derive_subkey(master_key, SubkeyPurpose::AuthVerifier)
+    }
+
     /// Changes the master password and returns the new salt
     pub fn change_master_password(
         &mut self,
@@ -93,7 +201,7 @@ impl KeyHierarchy {
         let old_master_key = self.key_derivation.derive_key(old_password, old_salt)?;
 
         println!("🔄 Comparing derived key with stored master key");
-        if old_master_key.as_ref() != self.master_key.as_ref() {
+        if !constant_time_eq(old_master_key.as_ref(), self.master_key.as_ref()) {
             println!("❌ Password verification failed: derived key doesn't match stored key");
             return Err(CryptoError::InvalidMasterPassword);
         }
@@ -115,7 +223,8 @@ impl KeyHierarchy {
         &mut self,
         master_password: &[u8],
         old_salt: &[u8],
-        new_key_derivation: KeyDerivation
+        new_key_derivation: KeyDerivation,
+        mek_aad: &[u8],
     ) -> Result<(Vec<u8>, EncryptedData), CryptoError> {
         println!("🔐 KeyHierarchy: Verifying master password");
         if !self.verify_master_password(master_password, old_salt)? {
@@ -133,11 +242,11 @@ impl KeyHierarchy {
         )?;
 
         println!("🔒 Re-encrypting MEK with new master key");
-        let encrypted_mek = self.encrypted_mek(new_master_key.as_ref())?;
+        let encrypted_mek = self.encrypted_mek(new_master_key.as_ref(), mek_aad)?;
 
         println!("✅ Validating new encryption by test decryption");
-        let decrypted_mek = Self::decrypt_mek_with_key(&encrypted_mek, new_master_key.as_ref())?;
-        if decrypted_mek.as_slice() != self.mek.as_ref() {
+        let decrypted_mek = Self::decrypt_mek_with_key(&encrypted_mek, new_master_key.as_ref(), mek_aad)?;
+        if !constant_time_eq(&decrypted_mek, self.mek.as_ref()) {
             println!("❌ Validation failed: MEK mismatch after re-encryption");
             return Err(CryptoError::KeyDerivationFailed);
         }
@@ -150,17 +259,21 @@ impl KeyHierarchy {
         Ok((new_salt, encrypted_mek))
     }
 
-    pub fn encrypted_mek(&self, master_key: &[u8]) -> Result<EncryptedData, CryptoError> {
+    /// Wraps the MEK with a key split from `master_key`; the master key itself never touches the MEK.
+    /// `aad` ties the wrap to the vault it belongs to, see `AuthService::mek_aad`.
+    pub fn encrypted_mek(&self, master_key: &[u8], aad: &[u8]) -> Result<EncryptedData, CryptoError> {
+        let wrapping_key = derive_subkey(master_key, SubkeyPurpose::MekWrap)?;
+
         let mut nonce_bytes = [0u8; 12];
         OsRng.fill_bytes(&mut nonce_bytes);
         let nonce = Nonce::try_from(&nonce_bytes[..])
             .map_err(|_| CryptoError::MekEncryptionFailed)?;
 
-        let cipher = Aes256Gcm::new_from_slice(master_key)
+        let cipher = Aes256Gcm::new_from_slice(wrapping_key.as_ref())
             .map_err(|_| CryptoError::MekEncryptionFailed)?;
 
         let ciphertext = cipher
-            .encrypt(&nonce, self.mek.as_ref().as_slice())
+            .encrypt(&nonce, Payload { msg: self.mek.as_ref().as_slice(), aad })
             .map_err(|_| CryptoError::MekEncryptionFailed)?;
 
         // Extract tag from ciphertext
@@ -173,8 +286,10 @@ impl KeyHierarchy {
         EncryptedData::new(ciphertext, nonce_bytes, tag)
     }
 
-    /// Encrypts data using the MEK
-    pub fn encrypt_data(&self, data: &[u8]) -> Result<EncryptedData, CryptoError> {
+    /// Encrypts data using the MEK.
+    /// `aad` is authenticated but not encrypted; the same bytes must be passed to `decrypt_data`,
+    /// which stops a ciphertext from being moved to another entry or field without detection.
+    pub fn encrypt_data(&self, data: &[u8], aad: &[u8]) -> Result<EncryptedData, CryptoError> {
         if data.is_empty() {
             return Err(CryptoError::EmptyData);
         }
@@ -188,7 +303,7 @@ impl KeyHierarchy {
             .map_err(|_| CryptoError::EncryptionFailed)?;
 
         let ciphertext = cipher
-            .encrypt(&nonce, data)
+            .encrypt(&nonce, Payload { msg: data, aad })
             .map_err(|_| CryptoError::EncryptionFailed)?;
 
         // The tag is the last 16 bytes of the ciphertext in AES-GCM
@@ -201,8 +316,11 @@ impl KeyHierarchy {
         EncryptedData::new(ciphertext, nonce_bytes, tag)
     }
 
-    /// Decrypts data using the MEK
-    pub fn decrypt_data(&self, encrypted: &EncryptedData) -> Result<Vec<u8>, CryptoError> {
+    /// Decrypts data using the MEK.
+    /// Blobs written before associated data existed are authenticated without it.
+    pub fn decrypt_data(&self, encrypted: &EncryptedData, aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let aad = if encrypted.is_aad_bound() { aad } else { &[] };
+
         let cipher = Aes256Gcm::new_from_slice(self.mek.as_ref())
             .map_err(|_| CryptoError::DecryptionFailed)?;
 
@@ -214,17 +332,88 @@ impl KeyHierarchy {
         ciphertext_with_tag.extend_from_slice(encrypted.tag());
 
         cipher
-            .decrypt(&nonce, ciphertext_with_tag.as_slice())
+            .decrypt(&nonce, Payload { msg: ciphertext_with_tag.as_slice(), aad })
             .map_err(|_| CryptoError::DecryptionFailed)
     }
 
+    /// Generates a random key for data that is encrypted outside the MEK, e.g. attachment files
+    pub fn generate_data_key() -> Result<SecureMemory<Vec<u8>>, CryptoError> {
+        Self::generate_mek()
+    }
+
+    /// Encrypts data with a caller-supplied key such as one from `generate_data_key`
+    pub fn encrypt_with_key(key: &[u8], data: &[u8], aad: &[u8]) -> Result<EncryptedData, CryptoError> {
+        if data.is_empty() {
+            return Err(CryptoError::EmptyData);
+        }
+
+        let mut nonce_bytes = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::try_from(&nonce_bytes[..])
+            .map_err(|_| CryptoError::EncryptionFailed)?;
+
+        let cipher = Aes256Gcm::new_from_slice(key)
+            .map_err(|_| CryptoError::EncryptionFailed)?;
+
+        let ciphertext = cipher
+            .encrypt(&nonce, Payload { msg: data, aad })
+            .map_err(|_| CryptoError::EncryptionFailed)?;
+
+        let tag_start = ciphertext.len() - 16;
+        let mut tag = [0u8; 16];
+        tag.copy_from_slice(&ciphertext[tag_start..]);
+
+        let ciphertext = ciphertext[..tag_start].to_vec();
+
+        EncryptedData::new(ciphertext, nonce_bytes, tag)
+    }
+
+    /// Decrypts data produced by `encrypt_with_key`
+    pub fn decrypt_with_key(key: &[u8], encrypted: &EncryptedData, aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let cipher = Aes256Gcm::new_from_slice(key)
+            .map_err(|_| CryptoError::DecryptionFailed)?;
+
+        let nonce = Nonce::try_from(encrypted.nonce().as_slice())
+            .map_err(|_| CryptoError::DecryptionFailed)?;
+
+        let mut ciphertext_with_tag = encrypted.ciphertext().to_vec();
+        ciphertext_with_tag.extend_from_slice(encrypted.tag());
+
+        cipher
+            .decrypt(&nonce, Payload { msg: ciphertext_with_tag.as_slice(), aad })
+            .map_err(|_| CryptoError::DecryptionFailed)
+    }
+
+    /// Computes an HMAC-SHA256 over `data` keyed with a subkey of the MEK
+    pub fn file_mac(&self, data: &[u8]) -> Result<[u8; 32], CryptoError> {
+        Ok(self.file_mac_state(data)?.finalize().into_bytes().into())
+    }
+
+    /// Checks a MAC produced by `file_mac` in constant time
+    pub fn verify_file_mac(&self, data: &[u8], tag: &[u8]) -> Result<bool, CryptoError> {
+        Ok(self.file_mac_state(data)?.verify_slice(tag).is_ok())
+    }
+
+    fn file_mac_state(&self, data: &[u8]) -> Result<Hmac<Sha256>, CryptoError> {
+        let mac_key = derive_subkey(self.mek.as_ref(), SubkeyPurpose::FileMac)?;
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(mac_key.as_ref())
+            .map_err(|_| CryptoError::InvalidKey)?;
+        mac.update(data);
+        Ok(mac)
+    }
+
     pub fn verify_master_password(&self, password: &[u8], salt: &[u8]) -> Result<bool, CryptoError> {
         let derived_key = self.key_derivation.derive_key(password, salt)?;
-        Ok(derived_key.as_ref() == self.master_key.as_ref())
+        Ok(constant_time_eq(derived_key.as_ref(), self.master_key.as_ref()))
     }
 
-    pub fn decrypt_mek_with_key(encrypted_mek: &EncryptedData, master_key: &[u8]) -> Result<Vec<u8>, CryptoError> {
-        let cipher = Aes256Gcm::new_from_slice(master_key)
+    pub fn decrypt_mek_with_key(encrypted_mek: &EncryptedData, master_key: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let wrapping_key = derive_subkey(master_key, SubkeyPurpose::MekWrap)?;
+        Self::unwrap_mek(encrypted_mek, wrapping_key.as_ref(), aad)
+    }
+
+    fn unwrap_mek(encrypted_mek: &EncryptedData, wrapping_key: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let cipher = Aes256Gcm::new_from_slice(wrapping_key)
             .map_err(|_| CryptoError::MekDecryptionFailed)?;
 
         let nonce = Nonce::try_from(encrypted_mek.nonce().as_slice())
@@ -235,7 +424,7 @@ impl KeyHierarchy {
         ciphertext_with_tag.extend_from_slice(encrypted_mek.tag());
 
         cipher
-            .decrypt(&nonce, ciphertext_with_tag.as_slice())
+            .decrypt(&nonce, Payload { msg: ciphertext_with_tag.as_slice(), aad })
             .map_err(|_| CryptoError::MekDecryptionFailed)
     }
 
@@ -245,4 +434,11 @@ impl KeyHierarchy {
         OsRng.fill_bytes(&mut mek);
         Ok(SecureMemory::new(mek))
     }
-}
\ No newline at end of file
+}
+
+impl Zeroize for KeyHierarchy {
+    fn zeroize(&mut self) {
+        self.master_key.zeroize();
+        self.mek.zeroize();
+    }
+}
@@ -2,11 +2,13 @@ pub(crate) mod secure;
 pub(crate) mod key_derivation;
 pub(crate) mod key_hierarchy;
 pub(crate) mod encrypted_data;
+pub(crate) mod subkey;
 
-pub use secure::SecureMemory;
+pub use secure::{SecureMemory, constant_time_eq};
 pub use key_derivation::KeyDerivation;
 pub use key_hierarchy::KeyHierarchy;
 pub use encrypted_data::EncryptedData;
+pub use subkey::{derive_subkey, SubkeyPurpose};
 
 // Key Derivation Constants
 pub const RECOMMENDED_MEMORY_COST: u32 = 46_080;  // 45 MiB in KiB
@@ -51,3 +51,11 @@ impl<T: Zeroize> fmt::Debug for SecureMemory<T> {
             .finish()
     }
 }
+
+/// Compares two byte slices in constant time.
+/// Only the length is allowed to leak; use this for anything derived from a secret.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    use subtle::ConstantTimeEq;
+
+    a.len() == b.len() && bool::from(a.ct_eq(b))
+}
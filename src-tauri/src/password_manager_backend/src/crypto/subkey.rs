@@ -0,0 +1,44 @@
+use hkdf::Hkdf;
+use sha2::Sha256;
+use crate::crypto::secure::SecureMemory;
+use crate::crypto::KEY_SIZE;
+use crate::error::crypto_error::CryptoError;
+
+/// Purposes a key can be split into.
+/// Each purpose has its own HKDF label, so learning one subkey reveals nothing about the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubkeyPurpose {
+    /// Wraps (encrypts) the MEK; never stored anywhere
+    MekWrap,
+    /// Stored in the vault to check the master password before unwrapping the MEK
+    AuthVerifier,
+    /// Keys the whole-file MAC; split from the MEK so it is only available after unlock
+    FileMac,
+    /// Keys the password hashes the security audit compares to find reuse; never stored
+    AuditHash,
+}
+
+impl SubkeyPurpose {
+    fn label(&self) -> &'static [u8] {
+        match self {
+            SubkeyPurpose::MekWrap => b"pawpass/mek-wrap/v1",
+            SubkeyPurpose::AuthVerifier => b"pawpass/auth-verifier/v1",
+            SubkeyPurpose::FileMac => b"pawpass/file-mac/v1",
+            SubkeyPurpose::AuditHash => b"pawpass/audit-hash/v1",
+        }
+    }
+}
+
+/// Derives an independent 256-bit subkey from `key` using HKDF-SHA256
+pub fn derive_subkey(key: &[u8], purpose: SubkeyPurpose) -> Result<SecureMemory<Vec<u8>>, CryptoError> {
+    if key.is_empty() {
+        return Err(CryptoError::InvalidKey);
+    }
+
+    let hkdf = Hkdf::<Sha256>::new(None, key);
+    let mut subkey = vec![0u8; KEY_SIZE];
+    hkdf.expand(purpose.label(), &mut subkey)
+        .map_err(|_| CryptoError::KeyDerivationFailed)?;
+
+    Ok(SecureMemory::new(subkey))
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zeroize::Zeroize;
+use crate::crypto::{EncryptedData, KeyHierarchy, SecureMemory};
+use crate::entry::{MAX_ATTACHMENT_NAME_LENGTH, MAX_ATTACHMENT_SIZE};
+use crate::error::app_error::AppError;
+use crate::error::entry_error::EntryError;
+
+// The file key is wrapped by the MEK and bound to the entry and attachment it belongs to
+/// Checks a file's size against the attachment limit, so oversized files are refused before being read
+pub fn check_size(size: u64) -> Result<(), AppError> {
+    if size > MAX_ATTACHMENT_SIZE as u64 {
+        return Err(EntryError::InvalidLength("Attachment".to_string()).into());
+    }
+    Ok(())
+}
+
+fn key_aad(entry_id: &Uuid, attachment_id: &Uuid) -> Vec<u8> {
+    format!("pawpass/attachment-key/{}/{}", entry_id, attachment_id).into_bytes()
+}
+
+fn content_aad(entry_id: &Uuid, attachment_id: &Uuid) -> Vec<u8> {
+    format!("pawpass/attachment/{}/{}", entry_id, attachment_id).into_bytes()
+}
+
+/// Reference from an entry to an encrypted file in the vault's `attachments/` directory.
+/// The file is encrypted with its own key, which is stored here wrapped by the MEK.
+#[derive(Serialize, Deserialize, Zeroize)]
+pub struct Attachment {
+    #[zeroize(skip)]
+    pub(crate) id: Uuid,
+    #[serde(rename = "fileName")]
+    file_name: String,
+    #[serde(rename = "mimeType")]
+    mime_type: Option<String>,
+    #[zeroize(skip)]
+    size: u64,
+    #[zeroize(skip)]
+    #[serde(rename = "addedAt")]
+    added_at: DateTime<Utc>,
+    wrapped_key: EncryptedData,
+}
+
+/// Metadata shown to the frontend; the content is only decrypted when the file is saved out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentInfo {
+    pub id: Uuid,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: Option<String>,
+    pub size: u64,
+    #[serde(rename = "addedAt")]
+    pub added_at: DateTime<Utc>,
+}
+
+impl Attachment {
+    /// Encrypts `content` under a fresh file key. Returns the reference to store on the entry
+    /// and the ciphertext to write to disk.
+    pub(crate) fn seal(
+        entry_id: &Uuid,
+        file_name: &str,
+        mime_type: Option<String>,
+        content: &[u8],
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<(Self, EncryptedData), EntryError> {
+        // Keep only the last path component so a name can never point outside the directory
+        let file_name = file_name
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .trim();
+        if file_name.is_empty() {
+            return Err(EntryError::ValidationError("Attachment name cannot be empty".to_string()));
+        }
+        if file_name.len() > MAX_ATTACHMENT_NAME_LENGTH {
+            return Err(EntryError::InvalidLength("Attachment name".to_string()));
+        }
+        if content.len() > MAX_ATTACHMENT_SIZE {
+            return Err(EntryError::InvalidLength("Attachment".to_string()));
+        }
+        if content.is_empty() {
+            return Err(EntryError::ValidationError("Attachment is empty".to_string()));
+        }
+
+        let id = Uuid::new_v4();
+        let file_key = KeyHierarchy::generate_data_key().map_err(EntryError::EncryptionError)?;
+
+        let ciphertext = KeyHierarchy::encrypt_with_key(file_key.as_ref(), content, &content_aad(entry_id, &id))
+            .map_err(EntryError::EncryptionError)?;
+        let wrapped_key = key_hierarchy
+            .encrypt_data(file_key.as_ref(), &key_aad(entry_id, &id))
+            .map_err(EntryError::EncryptionError)?;
+
+        let attachment = Self {
+            id,
+            file_name: file_name.to_string(),
+            mime_type,
+            size: content.len() as u64,
+            added_at: Utc::now(),
+            wrapped_key,
+        };
+
+        Ok((attachment, ciphertext))
+    }
+
+    /// Unwraps the file key and decrypts the attachment's content
+    pub(crate) fn open(
+        &self,
+        entry_id: &Uuid,
+        ciphertext: &EncryptedData,
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<SecureMemory<Vec<u8>>, EntryError> {
+        let file_key = SecureMemory::new(
+            key_hierarchy
+                .decrypt_data(&self.wrapped_key, &key_aad(entry_id, &self.id))
+                .map_err(EntryError::DecryptionError)?
+        );
+
+        KeyHierarchy::decrypt_with_key(file_key.as_ref(), ciphertext, &content_aad(entry_id, &self.id))
+            .map(SecureMemory::new)
+            .map_err(EntryError::DecryptionError)
+    }
+
+    pub fn info(&self) -> AttachmentInfo {
+        AttachmentInfo {
+            id: self.id,
+            file_name: self.file_name.clone(),
+            mime_type: self.mime_type.clone(),
+            size: self.size,
+            added_at: self.added_at,
+        }
+    }
+
+    pub fn file_name(&self) -> &str { &self.file_name }
+}
@@ -1,13 +1,20 @@
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
+use zeroize::Zeroize;
 
-use crate::crypto::KeyHierarchy;
+use crate::_vault::password_rules::{GeneratorProfile, GeneratorProfiles};
+use crate::crypto::{EncryptedData, KeyHierarchy, SecureMemory};
 use crate::category_favorite::category_collection::CategoryCollection;
 use crate::category_favorite::favorite_collection::FavoriteCollection;
 use crate::entry::Entry;
 use crate::entry::entry::DecryptedEntry;
+use crate::entry::attachment::AttachmentInfo;
+use crate::entry::history::DecryptedHistoryRecord;
 use crate::entry::overview::{EntryData, EntryOverview};
+use crate::entry::totp::TotpCode;
+use crate::error::app_error::AppError;
 use crate::error::entry_error::EntryError;
 use crate::entry::search::{SearchIndex, SearchQuery};
 
@@ -42,8 +49,13 @@ pub struct EntryCollection {
     pub entries: HashMap<Uuid, Entry>,
     pub categories: CategoryCollection,
     pub favorites: FavoriteCollection,
+    // Derived from the entries, so it is never persisted; rebuilt after the vault is unsealed
+    #[serde(skip)]
     pub search_index: SearchIndex,
     pub entry_count: usize,
+    // Kept with the entries so profile names, which tend to name sites, stay sealed
+    #[serde(default)]
+    pub generator_profiles: GeneratorProfiles,
 }
 
 impl EntryCollection {
@@ -54,6 +66,7 @@ impl EntryCollection {
             favorites: FavoriteCollection::new(),
             search_index: SearchIndex::new(),
             entry_count: 0,
+            generator_profiles: GeneratorProfiles::default(),
         }
     }
 
@@ -73,8 +86,17 @@ impl EntryCollection {
 
         let entry = Entry::new_internal(entry_data, category_id, key_hierarchy)?;
         let id = entry.id;
+        self.insert_entry(entry)?;
 
-        if let Some(cat_id) = category_id {
+        Ok(id)
+    }
+
+    /// Adds an existing entry, e.g. one carried over from another copy of the vault.
+    /// Its `category_id` must refer to a category of this collection.
+    pub(crate) fn insert_entry(&mut self, entry: Entry) -> Result<(), EntryError> {
+        let id = entry.id;
+
+        if let Some(cat_id) = entry.category_id {
             self.categories.add_entry_to_category(&cat_id, id)?;
         }
 
@@ -82,11 +104,11 @@ impl EntryCollection {
             self.favorites.add_favorite(id);
         }
 
-        self.add_to_search_indexes(entry.id, &entry.title, &entry.username, &entry.url);
+        self.add_to_search_indexes(entry.id, &entry.title, &entry.username, &entry.url, &entry.searchable_custom_values());
         self.entries.insert(id, entry);
         self.entry_count += 1;
 
-        Ok(id)
+        Ok(())
     }
 
     pub fn update_entry(
@@ -107,12 +129,13 @@ impl EntryCollection {
         };
 
         let entry = self.entries.get(id).ok_or(EntryError::NotFound(*id))?;
-        let old_data: (Option<Uuid>, bool, String, Option<String>, Option<String>) = (
+        let old_data: (Option<Uuid>, bool, String, Option<String>, Option<String>, Vec<String>) = (
             entry.category_id,
             entry.favorite,
             entry.title.clone(),
             entry.username.clone(),
-            entry.url.clone()
+            entry.url.clone(),
+            entry.searchable_custom_values()
         );
 
         if old_data.0 != category_id {
@@ -136,20 +159,22 @@ impl EntryCollection {
             *id,
             &old_data.2,
             &old_data.3,
-            &old_data.4
+            &old_data.4,
+            &old_data.5
         );
 
-        let (new_title, new_username, new_url) = {
+        let (new_title, new_username, new_url, new_custom_values) = {
             let entry = self.entries.get_mut(id).ok_or(EntryError::NotFound(*id))?;
             entry.update_internal(changes, category_id, key_hierarchy)?;
             (
                 entry.title.clone(),
                 entry.username.clone(),
-                entry.url.clone()
+                entry.url.clone(),
+                entry.searchable_custom_values()
             )
         };
 
-        self.add_to_search_indexes(*id, &new_title, &new_username, &new_url);
+        self.add_to_search_indexes(*id, &new_title, &new_username, &new_url, &new_custom_values);
 
         Ok(())
     }
@@ -161,9 +186,13 @@ impl EntryCollection {
         let title = entry.title.clone();
         let username = entry.username.clone();
         let url = entry.url.clone();
+        let custom_values = entry.searchable_custom_values();
         let was_favorite = entry.favorite;
 
-        self.entries.remove(id);
+        // Wipe the ciphertexts, including every history version, before the memory is released
+        if let Some(mut removed) = self.entries.remove(id) {
+            removed.zeroize();
+        }
         self.entry_count -= 1;
 
         if let Some(category_id) = category_id {
@@ -174,7 +203,7 @@ impl EntryCollection {
             self.favorites.remove_favorite(id);
         }
 
-        self.remove_from_search_indexes_data(*id, &title, &username, &url);
+        self.remove_from_search_indexes_data(*id, &title, &username, &url, &custom_values);
 
         Ok(())
     }
@@ -188,6 +217,67 @@ impl EntryCollection {
         entry.decrypt(key_hierarchy)
     }
 
+    /// Current one-time code for an entry with a TOTP secret
+    pub fn get_totp_code(&self, id: &Uuid, key_hierarchy: &KeyHierarchy) -> Result<TotpCode, EntryError> {
+        let entry = self.entries.get(id).ok_or(EntryError::NotFound(*id))?;
+        // Clocks before 1970 would only produce garbage codes; clamp instead of failing
+        let now = Utc::now().timestamp().max(0) as u64;
+        entry.totp_code(key_hierarchy, now)
+    }
+
+    /// Lists the previous versions of an entry's password and notes, newest first
+    pub fn get_entry_history(
+        &self,
+        id: &Uuid,
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<Vec<DecryptedHistoryRecord>, EntryError> {
+        let entry = self.entries.get(id).ok_or(EntryError::NotFound(*id))?;
+        entry.decrypt_history(key_hierarchy)
+    }
+
+    /// Restores a previous version of an entry's password and notes
+    pub fn restore_entry_history(&mut self, id: &Uuid, version_id: &Uuid) -> Result<(), EntryError> {
+        let entry = self.entries.get_mut(id).ok_or(EntryError::NotFound(*id))?;
+        entry.restore_history(version_id)
+    }
+
+    /// Encrypts a file for an entry; the returned ciphertext still has to be written by `VaultStorage`
+    pub fn add_attachment(
+        &mut self,
+        id: &Uuid,
+        file_name: &str,
+        mime_type: Option<String>,
+        content: &[u8],
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<(AttachmentInfo, EncryptedData), EntryError> {
+        let entry = self.entries.get_mut(id).ok_or(EntryError::NotFound(*id))?;
+        entry.add_attachment(file_name, mime_type, content, key_hierarchy)
+    }
+
+    pub fn remove_attachment(&mut self, id: &Uuid, attachment_id: &Uuid) -> Result<(), EntryError> {
+        let entry = self.entries.get_mut(id).ok_or(EntryError::NotFound(*id))?;
+        entry.remove_attachment(attachment_id)
+    }
+
+    pub fn open_attachment(
+        &self,
+        id: &Uuid,
+        attachment_id: &Uuid,
+        ciphertext: &EncryptedData,
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<SecureMemory<Vec<u8>>, EntryError> {
+        let entry = self.entries.get(id).ok_or(EntryError::NotFound(*id))?;
+        entry.open_attachment(attachment_id, ciphertext, key_hierarchy)
+    }
+
+    /// Every attachment still referenced by an entry; files not in this set are orphans
+    pub fn attachment_ids(&self) -> HashSet<Uuid> {
+        self.entries
+            .values()
+            .flat_map(|entry| entry.attachment_ids())
+            .collect()
+    }
+
     pub fn get_entry_overview(&self, id: &Uuid) -> Result<EntryOverview, EntryError> {
         let entry = self.entries.get(id).ok_or(EntryError::NotFound(*id))?;
         Ok(entry.to_overview())
@@ -202,13 +292,69 @@ impl EntryCollection {
 }
 
 impl EntryCollection {
+    /// Attaches a generator profile to an entry, or detaches it with `None`
+    pub fn set_entry_generator_profile(&mut self, id: &Uuid, profile_id: Option<Uuid>) -> Result<(), AppError> {
+        if let Some(profile_id) = &profile_id {
+            self.generator_profiles.get(profile_id)?;
+        }
+        let entry = self.entries.get_mut(id).ok_or(EntryError::NotFound(*id))?;
+        entry.generator_profile_id = profile_id;
+        Ok(())
+    }
+
+    /// The profile attached to an entry, if any
+    pub fn entry_generator_profile(&self, id: &Uuid) -> Result<Option<&GeneratorProfile>, AppError> {
+        let entry = self.entries.get(id).ok_or(EntryError::NotFound(*id))?;
+        match &entry.generator_profile_id {
+            Some(profile_id) => Ok(Some(self.generator_profiles.get(profile_id)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Deletes a generator profile and detaches it from every entry using it
+    pub fn delete_generator_profile(&mut self, profile_id: &Uuid) -> Result<(), AppError> {
+        self.generator_profiles.remove(profile_id)?;
+        for entry in self.entries.values_mut() {
+            if entry.generator_profile_id == Some(*profile_id) {
+                entry.generator_profile_id = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the search index from scratch using the current entries
+    pub fn rebuild_search_index(&mut self) {
+        let mut search_index = SearchIndex::new();
+        for entry in self.entries.values() {
+            search_index.add_entry(entry.id, &entry.title, &entry.username, &entry.url, &entry.searchable_custom_values());
+        }
+        self.search_index = search_index;
+    }
+
+    /// Re-encrypts sensitive fields written before they were bound to their entry.
+    /// Returns how many entries were updated.
+    pub fn rebind_sensitive_data(&mut self, key_hierarchy: &KeyHierarchy) -> Result<usize, EntryError> {
+        let mut rebound = 0;
+        for entry in self.entries.values_mut() {
+            let mut changed = entry.sensitive_data.rebind(&entry.id, key_hierarchy)?;
+            for record in &mut entry.history {
+                changed |= record.sensitive_data.rebind(&entry.id, key_hierarchy)?;
+            }
+            if changed {
+                rebound += 1;
+            }
+        }
+        Ok(rebound)
+    }
+
     fn add_to_search_indexes(
         &mut self,
         id: Uuid,
         title: &str,
         username: &Option<String>,
-        url: &Option<String>,) {
-        self.search_index.add_entry(id, title, username, url);
+        url: &Option<String>,
+        custom_values: &[String]) {
+        self.search_index.add_entry(id, title, username, url, custom_values);
     }
 
     fn remove_from_search_indexes_data(
@@ -217,8 +363,9 @@ impl EntryCollection {
         title: &str,
         username: &Option<String>,
         url: &Option<String>,
+        custom_values: &[String],
     ) {
-        self.search_index.remove_entry(id, title, username, url);
+        self.search_index.remove_entry(id, title, username, url, custom_values);
     }
 }
 
@@ -241,6 +388,12 @@ impl EntryCollection {
             max_count = result_ids.len();
         }
 
+        // Apply kind filter
+        if let Some(kind) = query.kind {
+            result_ids.retain(|id| self.entries.get(id).is_some_and(|entry| entry.kind == kind));
+            max_count = result_ids.len();
+        }
+
         // Apply category filter last
         if let Some(category_name) = &query.category_name {
             if let Ok(category_entries) = self.categories.get_entries_in_category(category_name.to_string()) {
@@ -0,0 +1,174 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::crypto::{EncryptedData, KeyHierarchy};
+use crate::entry::{MAX_CUSTOM_FIELD_NAME_LENGTH, MAX_CUSTOM_FIELD_VALUE_LENGTH};
+use crate::entry::overview::validate_url;
+use crate::entry::sensitive_data::field_aad;
+use crate::error::crypto_error::CryptoError::DecryptionFailed;
+use crate::error::entry_error::EntryError;
+
+/// Kind of value a custom field holds; decides how it is validated and whether it is encrypted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CustomFieldType {
+    Text,
+    // Encrypted at rest and never indexed, e.g. security answers or API keys
+    Hidden,
+    Url,
+    Email,
+    // ISO 8601 calendar date (YYYY-MM-DD)
+    Date,
+}
+
+/// A custom field as sent by and returned to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct CustomFieldData {
+    pub name: String,
+    #[zeroize(skip)]
+    #[serde(rename = "fieldType")]
+    pub field_type: CustomFieldType,
+    pub value: String,
+    // Visible fields only; lets the value be found through `search_entries`
+    #[serde(default)]
+    pub searchable: bool,
+}
+
+impl CustomFieldData {
+    /// Validates the field name and checks the value against its type
+    pub fn validate(&self) -> Result<(), EntryError> {
+        if self.name.trim().is_empty() {
+            return Err(EntryError::InvalidCustomField("Field name cannot be empty".to_string()));
+        }
+        if self.name.len() > MAX_CUSTOM_FIELD_NAME_LENGTH {
+            return Err(EntryError::InvalidLength("Custom field name".to_string()));
+        }
+        if self.value.len() > MAX_CUSTOM_FIELD_VALUE_LENGTH {
+            return Err(EntryError::InvalidLength(format!("Custom field '{}'", self.name)));
+        }
+        if self.field_type == CustomFieldType::Hidden && self.searchable {
+            return Err(EntryError::InvalidCustomField(
+                format!("Hidden field '{}' cannot be searchable", self.name)
+            ));
+        }
+
+        // Empty values are allowed so fields can be added before they are filled in
+        if self.value.is_empty() {
+            return Ok(());
+        }
+
+        match self.field_type {
+            CustomFieldType::Text | CustomFieldType::Hidden => Ok(()),
+            CustomFieldType::Url => validate_url(&self.value),
+            CustomFieldType::Email => {
+                if is_valid_email(&self.value) {
+                    Ok(())
+                } else {
+                    Err(EntryError::InvalidCustomField(format!("'{}' is not a valid email address", self.name)))
+                }
+            }
+            CustomFieldType::Date => NaiveDate::parse_from_str(&self.value, "%Y-%m-%d")
+                .map(|_| ())
+                .map_err(|_| EntryError::InvalidCustomField(
+                    format!("'{}' must be a date in YYYY-MM-DD format", self.name)
+                )),
+        }
+    }
+}
+
+// Deliberately loose: one '@', something on both sides and a dot in the domain
+pub(crate) fn is_valid_email(value: &str) -> bool {
+    if value.chars().any(char::is_whitespace) {
+        return false;
+    }
+    match value.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.split('.').count() > 1
+                && domain.split('.').all(|label| !label.is_empty())
+        }
+        None => false,
+    }
+}
+
+#[derive(Serialize, Deserialize, Zeroize)]
+enum CustomFieldValue {
+    Visible(String),
+    // None for an empty hidden field, since empty ciphertexts are rejected
+    Hidden(Option<EncryptedData>),
+}
+
+/// A custom field as stored in the vault. Hidden values are bound to the entry and to this field's id.
+#[derive(Serialize, Deserialize, Zeroize)]
+pub struct CustomField {
+    #[zeroize(skip)]
+    id: Uuid,
+    name: String,
+    #[zeroize(skip)]
+    #[serde(rename = "fieldType")]
+    field_type: CustomFieldType,
+    #[zeroize(skip)]
+    searchable: bool,
+    value: CustomFieldValue,
+}
+
+impl CustomField {
+    /// Stores a validated field, encrypting hidden values
+    pub(crate) fn new(entry_id: &Uuid, data: CustomFieldData, key_hierarchy: &KeyHierarchy) -> Result<Self, EntryError> {
+        let id = Uuid::new_v4();
+
+        let value = match data.field_type {
+            CustomFieldType::Hidden if data.value.is_empty() => CustomFieldValue::Hidden(None),
+            CustomFieldType::Hidden => CustomFieldValue::Hidden(Some(
+                key_hierarchy
+                    .encrypt_data(data.value.as_bytes(), &Self::aad(entry_id, &id))
+                    .map_err(EntryError::EncryptionError)?
+            )),
+            _ => CustomFieldValue::Visible(data.value.clone()),
+        };
+
+        Ok(Self {
+            id,
+            name: data.name.clone(),
+            field_type: data.field_type,
+            searchable: data.searchable,
+            value,
+        })
+    }
+
+    fn aad(entry_id: &Uuid, field_id: &Uuid) -> Vec<u8> {
+        field_aad(entry_id, &format!("custom/{}", field_id))
+    }
+
+    /// The value to put in the search index, if this field is searchable
+    pub(crate) fn searchable_value(&self) -> Option<&str> {
+        match &self.value {
+            CustomFieldValue::Visible(value) if self.searchable && !value.is_empty() => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the field in the same shape it was created from
+    pub(crate) fn decrypt(&self, entry_id: &Uuid, key_hierarchy: &KeyHierarchy) -> Result<CustomFieldData, EntryError> {
+        let value = match &self.value {
+            CustomFieldValue::Visible(value) => value.clone(),
+            CustomFieldValue::Hidden(None) => String::new(),
+            CustomFieldValue::Hidden(Some(encrypted)) => {
+                let bytes = key_hierarchy
+                    .decrypt_data(encrypted, &Self::aad(entry_id, &self.id))
+                    .map_err(EntryError::DecryptionError)?;
+                String::from_utf8(bytes)
+                    .map_err(|_| EntryError::DecryptionError(DecryptionFailed))?
+            }
+        };
+
+        Ok(CustomFieldData {
+            name: self.name.clone(),
+            field_type: self.field_type,
+            value,
+            searchable: self.searchable,
+        })
+    }
+}
@@ -2,11 +2,18 @@ use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 use zeroize::{Zeroize, ZeroizeOnDrop};
-use crate::crypto::KeyHierarchy;
+use crate::crypto::{EncryptedData, KeyHierarchy, SecureMemory};
 use crate::error::entry_error::EntryError;
 use crate::entry::{
+    MAX_ATTACHMENTS_PER_ENTRY,
+    MAX_HISTORY_RECORDS,
     sensitive_data::{EncryptedSensitiveData, DecryptedSensitiveData},
-    overview::{EntryOverview, EntryData}
+    history::{HistoryRecord, DecryptedHistoryRecord},
+    overview::{EntryOverview, EntryData},
+    totp::{TotpCode, TotpConfig},
+    custom_field::{CustomField, CustomFieldData},
+    kind::{EntryDetails, EntryKind},
+    attachment::{Attachment, AttachmentInfo}
 };
 
 
@@ -23,6 +30,8 @@ pub struct Entry {
     #[serde(rename = "categoryName")]
     pub(crate) category_name: Option<String>,
     pub(crate) favorite: bool,
+    #[serde(default)]
+    pub(crate) kind: EntryKind,
 
     // Timestamps
     #[serde(rename = "createdAt")]
@@ -32,6 +41,22 @@ pub struct Entry {
 
     // Encrypted sensitive data
     pub(crate) sensitive_data: EncryptedSensitiveData,
+
+    // Previous versions of `sensitive_data`, oldest first
+    #[serde(default)]
+    pub(crate) history: Vec<HistoryRecord>,
+
+    // User-defined fields; hidden values are encrypted like `sensitive_data`
+    #[serde(default, rename = "customFields")]
+    pub(crate) custom_fields: Vec<CustomField>,
+
+    // Files in the vault's `attachments/` directory; added and removed separately from updates
+    #[serde(default)]
+    pub(crate) attachments: Vec<Attachment>,
+
+    // Site rules followed when the password is regenerated; set separately from updates
+    #[serde(default, rename = "generatorProfileId")]
+    pub(crate) generator_profile_id: Option<Uuid>,
 }
 
 
@@ -46,21 +71,37 @@ impl Entry {
 
         // only need to use now() once and will make sure that the timestamps match
         let now = Utc::now();
+        // The id is generated up front because the encrypted fields are bound to it
+        let id = Uuid::new_v4();
 
         Ok(Self {
-            id: Uuid::new_v4(),
+            id,
             title: input.title,
             username: input.username,
             url: input.url,
             category_id: category_id,
             category_name: input.category_name.map(|s| s.to_string()),
             favorite: input.favorite,
+            kind: input.kind,
             created_at: now,
             updated_at: now,
-            sensitive_data: EncryptedSensitiveData::new(input.password, input.notes, key_hierarchy)?,
+            sensitive_data: EncryptedSensitiveData::new(
+                &id, input.password, input.notes, input.totp, input.details.as_ref(), key_hierarchy
+            )?,
+            history: Vec::new(),
+            custom_fields: Self::store_custom_fields(&id, input.custom_fields, key_hierarchy)?,
+            attachments: Vec::new(),
+            generator_profile_id: None,
         })
     }
 
+    fn store_custom_fields(id: &Uuid, fields: Vec<CustomFieldData>, key_hierarchy: &KeyHierarchy) -> Result<Vec<CustomField>, EntryError> {
+        fields
+            .into_iter()
+            .map(|field| CustomField::new(id, field, key_hierarchy))
+            .collect()
+    }
+
     /// Update the Entry; only meant to be called by EntryCollection
     pub(crate) fn update_internal(&mut self,
                                   changes: EntryData,
@@ -69,6 +110,28 @@ impl Entry {
         -> Result<(), EntryError> {
         changes.validate()?;
 
+        let now = Utc::now();
+
+        // Everything fallible happens before `self` is touched, so a failed update leaves the entry intact
+        let unchanged = self.sensitive_data.matches(
+            &self.id, &changes.password, &changes.notes, &changes.totp, changes.details.as_ref(), key_hierarchy
+        )?;
+        let sensitive_data = EncryptedSensitiveData::new(
+            &self.id, changes.password, changes.notes, changes.totp, changes.details.as_ref(), key_hierarchy
+        )?;
+        let custom_fields = Self::store_custom_fields(&self.id, changes.custom_fields, key_hierarchy)?;
+
+        // Keep the outgoing sensitive fields when any of them changes
+        let mut history = std::mem::take(&mut self.history);
+        if !unchanged {
+            let previous = std::mem::replace(
+                &mut self.sensitive_data,
+                EncryptedSensitiveData { password: None, notes: None, totp: None, details: None }
+            );
+            history.push(HistoryRecord::new(previous, now));
+            Self::trim_history(&mut history);
+        }
+
         *self = Self {
             id: self.id,
             title: changes.title,
@@ -77,25 +140,91 @@ impl Entry {
             category_id: category_id,
             category_name: changes.category_name.map(|s| s.to_string()),
             favorite: changes.favorite,
+            kind: changes.kind,
             created_at: self.created_at,
-            updated_at: Utc::now(),
-            sensitive_data: EncryptedSensitiveData::new(changes.password, changes.notes, key_hierarchy)?,
+            updated_at: now,
+            sensitive_data,
+            history,
+            custom_fields,
+            attachments: std::mem::take(&mut self.attachments),
+            generator_profile_id: self.generator_profile_id,
         };
 
         Ok(())
     }
 
+    /// Makes a history version current again; the version it replaces moves into history,
+    /// so a restore can itself be undone.
+    pub(crate) fn restore_history(&mut self, version_id: &Uuid) -> Result<(), EntryError> {
+        let index = self.history
+            .iter()
+            .position(|record| record.id == *version_id)
+            .ok_or(EntryError::HistoryNotFound(*version_id))?;
+
+        let now = Utc::now();
+        let restored = self.history.remove(index);
+
+        // History blobs are bound to this entry's id, so they can be swapped back in without re-encrypting
+        let previous = std::mem::replace(&mut self.sensitive_data, restored.sensitive_data);
+        self.history.push(HistoryRecord::new(previous, now));
+        Self::trim_history(&mut self.history);
+        self.updated_at = now;
+
+        Ok(())
+    }
+
+    /// Decrypts every stored version, newest first
+    pub fn decrypt_history(&self, key_hierarchy: &KeyHierarchy) -> Result<Vec<DecryptedHistoryRecord>, EntryError> {
+        self.history
+            .iter()
+            .rev()
+            .map(|record| {
+                let (password, notes, totp, details) = record.sensitive_data.decrypt(&self.id, key_hierarchy)?
+                    .map(|data: DecryptedSensitiveData| {
+                        (
+                            data.password.map(|p| unsafe{ p.into_inner() }),
+                            data.notes.map(|n| unsafe{ n.into_inner() }),
+                            data.totp.map(|t| unsafe{ t.into_inner() }),
+                            data.details
+                        )
+                    })
+                    .unwrap_or((None, None, None, None));
+
+                Ok(DecryptedHistoryRecord {
+                    id: record.id,
+                    replaced_at: record.replaced_at,
+                    password,
+                    notes,
+                    totp,
+                    details,
+                })
+            })
+            .collect()
+    }
+
+    // Drops the oldest versions beyond the limit; removed records zeroize their ciphertexts
+    fn trim_history(history: &mut Vec<HistoryRecord>) {
+        if history.len() > MAX_HISTORY_RECORDS {
+            let excess = history.len() - MAX_HISTORY_RECORDS;
+            for mut record in history.drain(..excess) {
+                record.zeroize();
+            }
+        }
+    }
+
     /// Securely and temporarily access encrypted data; used in Tauri Commands
     pub fn decrypt(&self, key_hierarchy: &KeyHierarchy) -> Result<DecryptedEntry, EntryError> {
-        // Destructure decrypted password and notes from our EncryptedSensitiveData object
-        let (password, notes) = self.sensitive_data.decrypt(key_hierarchy)?
+        // Destructure decrypted password, notes, TOTP secret and details from our EncryptedSensitiveData object
+        let (password, notes, totp, details) = self.sensitive_data.decrypt(&self.id, key_hierarchy)?
             .map(|data: DecryptedSensitiveData| {
                 (
                     data.password.map(|p| unsafe{ p.into_inner() }),
-                    data.notes.map(|n| unsafe{ n.into_inner() })
+                    data.notes.map(|n| unsafe{ n.into_inner() }),
+                    data.totp.map(|t| unsafe{ t.into_inner() }),
+                    data.details
                 )
             })
-            .unwrap_or((None, None));
+            .unwrap_or((None, None, None, None));
 
         Ok(DecryptedEntry {
             id: self.id,
@@ -105,13 +234,98 @@ impl Entry {
             category_id: self.category_id,
             category_name: self.category_name.clone(),
             favorite: self.favorite,
+            kind: self.kind,
             created_at: self.created_at,
             updated_at: self.updated_at,
             password,
             notes,
+            totp,
+            details,
+            custom_fields: self.custom_fields
+                .iter()
+                .map(|field| field.decrypt(&self.id, key_hierarchy))
+                .collect::<Result<_, _>>()?,
+            attachments: self.attachments.iter().map(Attachment::info).collect(),
+            generator_profile_id: self.generator_profile_id,
         })
     }
 
+    /// Encrypts a new attachment and records it on the entry.
+    /// Returns the ciphertext, which the caller writes to the attachments directory.
+    pub(crate) fn add_attachment(
+        &mut self,
+        file_name: &str,
+        mime_type: Option<String>,
+        content: &[u8],
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<(AttachmentInfo, EncryptedData), EntryError> {
+        if self.attachments.len() >= MAX_ATTACHMENTS_PER_ENTRY {
+            return Err(EntryError::ValidationError(
+                format!("An entry can have at most {} attachments", MAX_ATTACHMENTS_PER_ENTRY)
+            ));
+        }
+
+        let (attachment, ciphertext) = Attachment::seal(&self.id, file_name, mime_type, content, key_hierarchy)?;
+        let info = attachment.info();
+        self.attachments.push(attachment);
+        self.updated_at = Utc::now();
+
+        Ok((info, ciphertext))
+    }
+
+    /// Drops the reference to an attachment; its file becomes an orphan for the caller to delete
+    pub(crate) fn remove_attachment(&mut self, attachment_id: &Uuid) -> Result<(), EntryError> {
+        let index = self.attachments
+            .iter()
+            .position(|attachment| attachment.id == *attachment_id)
+            .ok_or(EntryError::AttachmentNotFound(*attachment_id))?;
+
+        self.attachments.remove(index).zeroize();
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Decrypts an attachment's file content read from disk
+    pub fn open_attachment(
+        &self,
+        attachment_id: &Uuid,
+        ciphertext: &EncryptedData,
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<SecureMemory<Vec<u8>>, EntryError> {
+        self.attachment(attachment_id)?.open(&self.id, ciphertext, key_hierarchy)
+    }
+
+    pub fn attachment(&self, attachment_id: &Uuid) -> Result<&Attachment, EntryError> {
+        self.attachments
+            .iter()
+            .find(|attachment| attachment.id == *attachment_id)
+            .ok_or(EntryError::AttachmentNotFound(*attachment_id))
+    }
+
+    pub fn attachment_ids(&self) -> Vec<Uuid> {
+        self.attachments.iter().map(|attachment| attachment.id).collect()
+    }
+
+    /// Values of the custom fields marked searchable, for the search index
+    pub(crate) fn searchable_custom_values(&self) -> Vec<String> {
+        self.custom_fields
+            .iter()
+            .filter_map(|field| field.searchable_value())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Computes the one-time code for `unix_time` from the entry's TOTP secret
+    pub fn totp_code(&self, key_hierarchy: &KeyHierarchy, unix_time: u64) -> Result<TotpCode, EntryError> {
+        let decrypted = self.sensitive_data.decrypt(&self.id, key_hierarchy)?;
+        let secret = decrypted
+            .as_ref()
+            .and_then(|data| data.totp.as_ref())
+            .ok_or(EntryError::TotpNotConfigured)?;
+
+        Ok(TotpConfig::parse(secret.as_ref())?.generate(unix_time))
+    }
+
     /// Created an EntryOverview object to display the entry on the dashboard
     pub(crate) fn to_overview(&self) -> EntryOverview {
         EntryOverview {
@@ -122,6 +336,7 @@ impl Entry {
             category_id: self.category_id,
             category_name: self.category_name.clone(),
             favorite: self.favorite,
+            kind: self.kind,
             created_at: self.created_at,
             updated_at: self.updated_at,
         }
@@ -143,6 +358,10 @@ impl Entry {
 
     pub fn favorite(&self) -> bool { self.favorite }
 
+    pub fn kind(&self) -> EntryKind { self.kind }
+
+    pub fn generator_profile_id(&self) -> Option<&Uuid> { self.generator_profile_id.as_ref() }
+
     pub fn created_at(&self) -> DateTime<Utc> { self.created_at }
 
     pub fn updated_at(&self) -> DateTime<Utc> { self.updated_at }
@@ -177,6 +396,15 @@ impl Zeroize for Entry {
         }
         // category_id and id are UUIDs and don't need zeroizing
         self.sensitive_data.zeroize();
+        for record in &mut self.history {
+            record.zeroize();
+        }
+        for field in &mut self.custom_fields {
+            field.zeroize();
+        }
+        for attachment in &mut self.attachments {
+            attachment.zeroize();
+        }
         // timestamps don't need zeroizing
     }
 }
@@ -195,12 +423,39 @@ pub struct DecryptedEntry {
     #[serde(rename = "categoryName")]
     pub category_name: Option<String>,
     pub favorite: bool,
+    pub kind: EntryKind,
     #[serde(rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(rename = "updatedAt")]
     pub updated_at: DateTime<Utc>,
     pub password: Option<String>,
     pub notes: Option<String>,
+    pub totp: Option<String>,
+    pub details: Option<EntryDetails>,
+    #[serde(rename = "customFields")]
+    pub custom_fields: Vec<CustomFieldData>,
+    pub attachments: Vec<AttachmentInfo>,
+    #[serde(rename = "generatorProfileId")]
+    pub generator_profile_id: Option<Uuid>,
+}
+
+impl DecryptedEntry {
+    /// The input that would recreate this entry; attachments and history are not included
+    pub fn to_entry_data(&self) -> EntryData {
+        EntryData {
+            title: self.title.clone(),
+            username: self.username.clone(),
+            password: self.password.clone(),
+            url: self.url.clone(),
+            notes: self.notes.clone(),
+            category_name: self.category_name.clone(),
+            favorite: self.favorite,
+            totp: self.totp.clone(),
+            custom_fields: self.custom_fields.clone(),
+            kind: self.kind,
+            details: self.details.clone(),
+        }
+    }
 }
 
 impl Zeroize for DecryptedEntry {
@@ -218,6 +473,15 @@ impl Zeroize for DecryptedEntry {
         if let Some(notes) = &mut self.notes {
             notes.zeroize();
         }
+        if let Some(totp) = &mut self.totp {
+            totp.zeroize();
+        }
+        for field in &mut self.custom_fields {
+            field.zeroize();
+        }
+        if let Some(details) = &mut self.details {
+            details.zeroize();
+        }
     }
 }
 
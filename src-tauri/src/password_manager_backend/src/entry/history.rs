@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::entry::kind::EntryDetails;
+use crate::entry::sensitive_data::EncryptedSensitiveData;
+
+/// A previous version of an entry's sensitive fields (password, notes, TOTP secret and details), still encrypted and bound to the entry.
+/// Recorded by `Entry::update_internal` whenever either field changes.
+#[derive(Serialize, Deserialize, Zeroize)]
+pub struct HistoryRecord {
+    #[zeroize(skip)]
+    pub(crate) id: Uuid,
+    #[zeroize(skip)]
+    #[serde(rename = "replacedAt")]
+    pub(crate) replaced_at: DateTime<Utc>,
+    pub(crate) sensitive_data: EncryptedSensitiveData,
+}
+
+impl HistoryRecord {
+    pub(crate) fn new(sensitive_data: EncryptedSensitiveData, replaced_at: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            replaced_at,
+            sensitive_data,
+        }
+    }
+}
+
+/// A history record decrypted for display; only created on demand
+#[derive(Serialize, Zeroize, ZeroizeOnDrop)]
+pub struct DecryptedHistoryRecord {
+    #[zeroize(skip)]
+    pub id: Uuid,
+    #[zeroize(skip)]
+    #[serde(rename = "replacedAt")]
+    pub replaced_at: DateTime<Utc>,
+    pub password: Option<String>,
+    pub notes: Option<String>,
+    pub totp: Option<String>,
+    pub details: Option<EntryDetails>,
+}
@@ -0,0 +1,232 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::entry::{MAX_DETAIL_FIELD_LENGTH, MAX_SSH_KEY_LENGTH, MAX_SSID_LENGTH};
+use crate::entry::custom_field::is_valid_email;
+use crate::error::entry_error::EntryError;
+
+/// What an entry represents. Existing entries are logins.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryKind {
+    #[default]
+    Login,
+    SecureNote,
+    PaymentCard,
+    Identity,
+    Wifi,
+    SshKey,
+}
+
+impl EntryKind {
+    /// Logins are the only kind that use username, password, URL and TOTP
+    pub fn uses_login_fields(&self) -> bool {
+        matches!(self, EntryKind::Login)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WifiSecurity {
+    Open,
+    Wep,
+    WpaPersonal,
+    WpaEnterprise,
+}
+
+/// Fields specific to a kind; the whole value is encrypted with the entry's sensitive data.
+/// Logins and secure notes have none.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum EntryDetails {
+    #[serde(rename_all = "camelCase")]
+    PaymentCard {
+        cardholder_name: Option<String>,
+        number: String,
+        expiry_month: u32,
+        expiry_year: u32,
+        security_code: Option<String>,
+        pin: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Identity {
+        full_name: Option<String>,
+        email: Option<String>,
+        phone: Option<String>,
+        address: Option<String>,
+        // YYYY-MM-DD
+        date_of_birth: Option<String>,
+        national_id: Option<String>,
+        passport_number: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Wifi {
+        ssid: String,
+        #[zeroize(skip)]
+        security: WifiSecurity,
+        passphrase: Option<String>,
+        hidden: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    SshKey {
+        private_key: String,
+        public_key: Option<String>,
+        passphrase: Option<String>,
+    },
+}
+
+impl EntryDetails {
+    pub fn kind(&self) -> EntryKind {
+        match self {
+            EntryDetails::PaymentCard { .. } => EntryKind::PaymentCard,
+            EntryDetails::Identity { .. } => EntryKind::Identity,
+            EntryDetails::Wifi { .. } => EntryKind::Wifi,
+            EntryDetails::SshKey { .. } => EntryKind::SshKey,
+        }
+    }
+
+    /// Checks the fields against the schema of their kind
+    pub fn validate(&self) -> Result<(), EntryError> {
+        match self {
+            EntryDetails::PaymentCard { cardholder_name, number, expiry_month, expiry_year, security_code, pin } => {
+                check_length("Cardholder name", cardholder_name.as_deref(), MAX_DETAIL_FIELD_LENGTH)?;
+                validate_card_number(number)?;
+                validate_expiry(*expiry_month, *expiry_year)?;
+                check_digits("Security code", security_code.as_deref(), 3..=4)?;
+                check_digits("PIN", pin.as_deref(), 4..=12)
+            }
+            EntryDetails::Identity { full_name, email, phone, address, date_of_birth, national_id, passport_number } => {
+                for (name, value) in [
+                    ("Full name", full_name),
+                    ("Phone", phone),
+                    ("Address", address),
+                    ("National ID", national_id),
+                    ("Passport number", passport_number),
+                ] {
+                    check_length(name, value.as_deref(), MAX_DETAIL_FIELD_LENGTH)?;
+                }
+                if let Some(email) = email.as_deref().filter(|email| !email.is_empty()) {
+                    if !is_valid_email(email) {
+                        return Err(EntryError::InvalidDetails("Email is not a valid email address".to_string()));
+                    }
+                }
+                if let Some(date) = date_of_birth.as_deref().filter(|date| !date.is_empty()) {
+                    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                        .map_err(|_| EntryError::InvalidDetails("Date of birth must be in YYYY-MM-DD format".to_string()))?;
+                    if date > Utc::now().date_naive() {
+                        return Err(EntryError::InvalidDetails("Date of birth is in the future".to_string()));
+                    }
+                }
+                Ok(())
+            }
+            EntryDetails::Wifi { ssid, security, passphrase, .. } => {
+                if ssid.is_empty() || ssid.len() > MAX_SSID_LENGTH {
+                    return Err(EntryError::InvalidDetails(
+                        format!("SSID must be between 1 and {} bytes", MAX_SSID_LENGTH)
+                    ));
+                }
+                let passphrase = passphrase.as_deref().unwrap_or_default();
+                match security {
+                    WifiSecurity::Open if !passphrase.is_empty() => Err(EntryError::InvalidDetails(
+                        "Open networks don't have a passphrase".to_string()
+                    )),
+                    // WPA/WPA2 pre-shared keys are 8-63 characters (or 64 hex digits)
+                    WifiSecurity::WpaPersonal if !(8..=64).contains(&passphrase.len()) => Err(EntryError::InvalidDetails(
+                        "WPA passphrases must be between 8 and 64 characters".to_string()
+                    )),
+                    _ => check_length("Passphrase", Some(passphrase), MAX_DETAIL_FIELD_LENGTH),
+                }
+            }
+            EntryDetails::SshKey { private_key, public_key, passphrase } => {
+                check_length("Private key", Some(private_key), MAX_SSH_KEY_LENGTH)?;
+                let private_key = private_key.trim();
+                if !(private_key.starts_with("-----BEGIN ") && private_key.contains("PRIVATE KEY-----")
+                    && private_key.ends_with("-----"))
+                {
+                    return Err(EntryError::InvalidDetails("Private key must be in PEM or OpenSSH format".to_string()));
+                }
+                if let Some(public_key) = public_key.as_deref().filter(|key| !key.is_empty()) {
+                    check_length("Public key", Some(public_key), MAX_SSH_KEY_LENGTH)?;
+                    validate_public_key(public_key)?;
+                }
+                check_length("Passphrase", passphrase.as_deref(), MAX_DETAIL_FIELD_LENGTH)
+            }
+        }
+    }
+}
+
+fn check_length(name: &str, value: Option<&str>, max: usize) -> Result<(), EntryError> {
+    match value {
+        Some(value) if value.len() > max => Err(EntryError::InvalidLength(name.to_string())),
+        _ => Ok(()),
+    }
+}
+
+fn check_digits(name: &str, value: Option<&str>, length: std::ops::RangeInclusive<usize>) -> Result<(), EntryError> {
+    match value.filter(|value| !value.is_empty()) {
+        Some(value) if !length.contains(&value.len()) || !value.bytes().all(|b| b.is_ascii_digit()) => {
+            Err(EntryError::InvalidDetails(
+                format!("{} must be {} to {} digits", name, length.start(), length.end())
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Card numbers are 12-19 digits and must pass the Luhn checksum; spaces and dashes are ignored
+fn validate_card_number(number: &str) -> Result<(), EntryError> {
+    let digits: Vec<u32> = number
+        .chars()
+        .filter(|c| *c != ' ' && *c != '-')
+        .map(|c| c.to_digit(10))
+        .collect::<Option<_>>()
+        .ok_or_else(|| EntryError::InvalidDetails("Card number can only contain digits".to_string()))?;
+
+    if !(12..=19).contains(&digits.len()) {
+        return Err(EntryError::InvalidDetails("Card number must be 12 to 19 digits".to_string()));
+    }
+
+    // Double every second digit from the right
+    let checksum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| if i % 2 == 1 { if d * 2 > 9 { d * 2 - 9 } else { d * 2 } } else { d })
+        .sum();
+
+    if !checksum.is_multiple_of(10) {
+        return Err(EntryError::InvalidDetails("Card number failed the Luhn check".to_string()));
+    }
+    Ok(())
+}
+
+// Expired cards are still accepted so old cards can be kept for reference
+fn validate_expiry(month: u32, year: u32) -> Result<(), EntryError> {
+    if !(1..=12).contains(&month) {
+        return Err(EntryError::InvalidDetails("Expiry month must be between 1 and 12".to_string()));
+    }
+    let current_year = Utc::now().year() as u32;
+    if !(2000..=current_year + 30).contains(&year) {
+        return Err(EntryError::InvalidDetails("Expiry year is out of range".to_string()));
+    }
+    Ok(())
+}
+
+// `<type> <base64> [comment]` as found in authorized_keys
+fn validate_public_key(key: &str) -> Result<(), EntryError> {
+    let mut parts = key.split_whitespace();
+    let key_type = parts.next().unwrap_or_default();
+    let body = parts.next().unwrap_or_default();
+
+    let known_type = key_type.starts_with("ssh-")
+        || key_type.starts_with("ecdsa-sha2-")
+        || key_type.starts_with("sk-");
+    let base64_body = !body.is_empty()
+        && body.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/' || b == b'=');
+
+    if known_type && base64_body {
+        Ok(())
+    } else {
+        Err(EntryError::InvalidDetails("Public key must be in OpenSSH format".to_string()))
+    }
+}
@@ -3,6 +3,11 @@ pub mod overview;
 pub mod entry;
 pub mod collection;
 pub mod search;
+pub mod history;
+pub mod totp;
+pub mod custom_field;
+pub mod kind;
+pub mod attachment;
 
 pub use crate::entry::entry::Entry;
 
@@ -12,3 +17,26 @@ pub const MAX_USERNAME_LENGTH: usize = 100;
 pub const MAX_URL_LENGTH: usize = 2048;
 pub const MAX_NOTES_LENGTH: usize = 10000;
 pub const MAX_PASS_CHAR_LENGTH: usize = 128;
+pub const MAX_HISTORY_RECORDS: usize = 10; // Oldest versions are dropped beyond this
+pub const MAX_CUSTOM_FIELDS: usize = 50;
+pub const MAX_CUSTOM_FIELD_NAME_LENGTH: usize = 100;
+pub const MAX_CUSTOM_FIELD_VALUE_LENGTH: usize = 1000;
+
+// Entry kind detail limits
+pub const MAX_DETAIL_FIELD_LENGTH: usize = 1000;
+pub const MAX_SSID_LENGTH: usize = 32; // IEEE 802.11 limit, in bytes
+pub const MAX_SSH_KEY_LENGTH: usize = 16384;
+
+// Attachment limits
+pub const MAX_ATTACHMENT_SIZE: usize = 25 * 1024 * 1024; // 25 MiB
+pub const MAX_ATTACHMENT_NAME_LENGTH: usize = 255;
+pub const MAX_ATTACHMENTS_PER_ENTRY: usize = 20;
+
+// TOTP Constants (RFC 6238 defaults)
+pub const MAX_TOTP_LENGTH: usize = 2048;
+pub const MIN_TOTP_SECRET_BYTES: usize = 10; // 80 bits, the RFC 4226 minimum
+pub const DEFAULT_TOTP_DIGITS: u32 = 6;
+pub const MIN_TOTP_DIGITS: u32 = 6;
+pub const MAX_TOTP_DIGITS: u32 = 10;
+pub const DEFAULT_TOTP_PERIOD: u64 = 30;
+pub const MAX_TOTP_PERIOD: u64 = 300;
@@ -2,7 +2,10 @@ use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use url::Url;
-use crate::entry::{MAX_NOTES_LENGTH, MAX_PASS_CHAR_LENGTH, MAX_TITLE_LENGTH, MAX_URL_LENGTH, MAX_USERNAME_LENGTH};
+use crate::entry::{MAX_CUSTOM_FIELDS, MAX_NOTES_LENGTH, MAX_PASS_CHAR_LENGTH, MAX_TITLE_LENGTH, MAX_URL_LENGTH, MAX_USERNAME_LENGTH};
+use crate::entry::custom_field::CustomFieldData;
+use crate::entry::kind::{EntryDetails, EntryKind};
+use crate::entry::totp::TotpConfig;
 use crate::error::entry_error::EntryError;
 
 /// Validates a URL string according to the password manager's requirements
@@ -29,6 +32,7 @@ pub struct EntryOverview {
     #[serde(rename = "categoryName")]
     pub category_name: Option<String>,
     pub favorite: bool,
+    pub kind: EntryKind,
     #[serde(rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(rename = "updatedAt")]
@@ -47,6 +51,16 @@ pub struct EntryData {
     #[serde(rename = "categoryName")]
     pub category_name: Option<String>,
     pub favorite: bool,
+    // otpauth:// URI or base32 secret
+    #[serde(default)]
+    pub totp: Option<String>,
+    #[serde(default, rename = "customFields")]
+    pub custom_fields: Vec<CustomFieldData>,
+    #[serde(default)]
+    pub kind: EntryKind,
+    // Required for payment cards, identities, Wi-Fi and SSH keys; must match `kind`
+    #[serde(default)]
+    pub details: Option<EntryDetails>,
 }
 
 impl EntryData {
@@ -86,6 +100,43 @@ impl EntryData {
             }
         }
 
+        // TOTP validation; parsing catches bad secrets now rather than when a code is requested
+        if let Some(totp) = self.totp.as_deref().filter(|totp| !totp.is_empty()) {
+            TotpConfig::parse(totp)?;
+        }
+
+        // Kind validation; only logins carry username, password, URL and TOTP
+        if !self.kind.uses_login_fields() {
+            let has_login_fields = [&self.username, &self.password, &self.url, &self.totp]
+                .iter()
+                .any(|field| field.as_deref().is_some_and(|value| !value.is_empty()));
+            if has_login_fields {
+                return Err(EntryError::InvalidDetails(
+                    "Only logins can have a username, password, URL or TOTP".to_string()
+                ));
+            }
+        }
+        match (&self.details, self.kind) {
+            (None, EntryKind::Login | EntryKind::SecureNote) => {}
+            (Some(details), kind) if details.kind() == kind => details.validate()?,
+            (Some(_), _) => {
+                return Err(EntryError::InvalidDetails("Details don't match the entry kind".to_string()));
+            }
+            (None, _) => {
+                return Err(EntryError::InvalidDetails("This kind of entry requires details".to_string()));
+            }
+        }
+
+        // Custom field validation
+        if self.custom_fields.len() > MAX_CUSTOM_FIELDS {
+            return Err(EntryError::InvalidCustomField(
+                format!("An entry can have at most {} custom fields", MAX_CUSTOM_FIELDS)
+            ));
+        }
+        for field in &self.custom_fields {
+            field.validate()?;
+        }
+
         Ok(())
     }
 }
\ No newline at end of file
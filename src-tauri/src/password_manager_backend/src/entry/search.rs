@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
+use crate::entry::kind::EntryKind;
 
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -8,6 +9,8 @@ pub struct SearchIndex {
     pub title_index: HashMap<String, HashSet<Uuid>>,
     pub username_index: HashMap<String, HashSet<Uuid>>,
     pub url_index: HashMap<String, HashSet<Uuid>>,
+    // Only custom fields the user marked searchable; hidden fields never get here
+    pub custom_field_index: HashMap<String, HashSet<Uuid>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,6 +20,9 @@ pub struct SearchQuery {
     pub category_name: Option<String>,
     #[serde(rename = "favoritesOnly")]
     pub favorites_only: bool,
+    // Restricts results to one kind of entry
+    #[serde(default)]
+    pub kind: Option<EntryKind>,
 }
 
 impl SearchQuery {
@@ -25,6 +31,7 @@ impl SearchQuery {
             text,
             category_name,
             favorites_only,
+            kind: None,
         }
     }
 }
@@ -34,7 +41,7 @@ impl SearchIndex {
         Self::default()
     }
 
-    pub fn add_entry(&mut self, id: Uuid, title: &str, username: &Option<String>, url: &Option<String>) {
+    pub fn add_entry(&mut self, id: Uuid, title: &str, username: &Option<String>, url: &Option<String>, custom_values: &[String]) {
         // Index title
         if !title.is_empty() {
             let key = title.to_lowercase();
@@ -65,9 +72,20 @@ impl SearchIndex {
                     .insert(id);
             }
         }
+
+        // Index searchable custom fields
+        for value in custom_values {
+            if !value.is_empty() {
+                let key = value.to_lowercase();
+                self.custom_field_index
+                    .entry(key)
+                    .or_default()
+                    .insert(id);
+            }
+        }
     }
 
-    pub fn remove_entry(&mut self, id: Uuid, title: &str, username: &Option<String>, url: &Option<String>) {
+    pub fn remove_entry(&mut self, id: Uuid, title: &str, username: &Option<String>, url: &Option<String>, custom_values: &[String]) {
         // Remove from title index
         if !title.is_empty() {
             let key = title.to_lowercase();
@@ -104,6 +122,19 @@ impl SearchIndex {
                 }
             }
         }
+
+        // Remove from custom field index
+        for value in custom_values {
+            if !value.is_empty() {
+                let key = value.to_lowercase();
+                if let Some(ids) = self.custom_field_index.get_mut(&key) {
+                    ids.remove(&id);
+                    if ids.is_empty() {
+                        self.custom_field_index.remove(&key);
+                    }
+                }
+            }
+        }
     }
 
     pub fn search_text(&self, text: &str) -> HashSet<Uuid> {
@@ -129,6 +160,12 @@ impl SearchIndex {
             }
         }
 
+        for (key, ids) in &self.custom_field_index {
+            if key.contains(&text_lower) {
+                matched_ids.extend(ids);
+            }
+        }
+
         matched_ids
     }
 }
\ No newline at end of file
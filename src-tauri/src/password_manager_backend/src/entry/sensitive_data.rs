@@ -1,19 +1,74 @@
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 use crate::crypto::{KeyHierarchy, EncryptedData, SecureMemory};
 use crate::entry::{MAX_NOTES_LENGTH, MAX_PASS_CHAR_LENGTH};
+use crate::entry::kind::EntryDetails;
 use crate::error::entry_error::EntryError;
 use crate::error::crypto_error::CryptoError::DecryptionFailed;
 
-fn encrypt_data(data: Option<String>, key_hierarchy: &KeyHierarchy) -> Result<Option<EncryptedData>, EntryError> {
+const PASSWORD_FIELD: &str = "password";
+const NOTES_FIELD: &str = "notes";
+const TOTP_FIELD: &str = "totp";
+const DETAILS_FIELD: &str = "details";
+
+/// Associated data binding a ciphertext to the entry and field it belongs to
+pub(crate) fn field_aad(entry_id: &Uuid, field: &str) -> Vec<u8> {
+    format!("pawpass/entry/{}/{}", entry_id, field).into_bytes()
+}
+
+fn decrypt_field(encrypted: &EncryptedData, aad: &[u8], key_hierarchy: &KeyHierarchy) -> Result<SecureMemory<String>, EntryError> {
+    let bytes = key_hierarchy
+        .decrypt_data(encrypted, aad)
+        .map_err(EntryError::DecryptionError)?;
+
+    String::from_utf8(bytes)
+        .map_err(|_| EntryError::DecryptionError(DecryptionFailed))
+        .map(SecureMemory::new)
+}
+
+fn encrypt_data(data: Option<String>, aad: &[u8], key_hierarchy: &KeyHierarchy) -> Result<Option<EncryptedData>, EntryError> {
     match data {
         Some(text) if !text.is_empty() => {
-            Ok(Some(key_hierarchy.encrypt_data(text.as_bytes()).map_err(EntryError::EncryptionError)?))
+            Ok(Some(key_hierarchy.encrypt_data(text.as_bytes(), aad).map_err(EntryError::EncryptionError)?))
         }
         _ => Ok(None)
     }
 }
 
+// Kind-specific details are stored as a single CBOR-encoded blob
+fn encrypt_details(details: Option<&EntryDetails>, aad: &[u8], key_hierarchy: &KeyHierarchy) -> Result<Option<EncryptedData>, EntryError> {
+    let Some(details) = details else {
+        return Ok(None);
+    };
+
+    let bytes = SecureMemory::new(
+        serde_cbor::to_vec(details)
+            .map_err(|_| EntryError::InvalidDetails("Details could not be serialized".to_string()))?
+    );
+    Ok(Some(key_hierarchy.encrypt_data(bytes.as_ref(), aad).map_err(EntryError::EncryptionError)?))
+}
+
+// Re-encrypts a blob written without associated data so it becomes bound to `aad`
+fn rebind_field(field: &mut Option<EncryptedData>, aad: &[u8], key_hierarchy: &KeyHierarchy) -> Result<bool, EntryError> {
+    let Some(encrypted) = field.as_ref().filter(|enc| !enc.is_aad_bound()) else {
+        return Ok(false);
+    };
+
+    let plaintext = SecureMemory::new(
+        key_hierarchy
+            .decrypt_data(encrypted, aad)
+            .map_err(EntryError::DecryptionError)?
+    );
+    *field = Some(
+        key_hierarchy
+            .encrypt_data(plaintext.as_ref(), aad)
+            .map_err(EntryError::EncryptionError)?
+    );
+
+    Ok(true)
+}
+
 /// Represents encrypted sensitive data fields of an entry.
 /// All fields are optional to support entries that may not have
 /// sensitive data or only have certain fields filled.
@@ -21,15 +76,25 @@ fn encrypt_data(data: Option<String>, key_hierarchy: &KeyHierarchy) -> Result<Op
 pub struct EncryptedSensitiveData {
     pub(crate) password: Option<EncryptedData>,
     pub(crate) notes: Option<EncryptedData>,
+    // otpauth:// URI or base32 secret; absent in vaults written before TOTP support
+    #[serde(default)]
+    pub(crate) totp: Option<EncryptedData>,
+    // Kind-specific fields such as card numbers or SSH keys
+    #[serde(default)]
+    pub(crate) details: Option<EncryptedData>,
 }
 
 impl EncryptedSensitiveData {
 
 
-    /// Creates a new EncryptedSensitiveData by encrypting the provided password and optional notes
+    /// Creates a new EncryptedSensitiveData by encrypting the provided password, notes, TOTP secret and details.
+    /// Each ciphertext is bound to `entry_id` and its field name, so it cannot be moved elsewhere.
     pub fn new(
+        entry_id: &Uuid,
         password: Option<String>,
         notes: Option<String>,
+        totp: Option<String>,
+        details: Option<&EntryDetails>,
         key_hierarchy: &KeyHierarchy,
     ) -> Result<Self, EntryError> {
 
@@ -45,43 +110,99 @@ impl EncryptedSensitiveData {
         }
 
         Ok(Self {
-            password: encrypt_data(password, key_hierarchy)?,
-            notes: encrypt_data(notes, key_hierarchy)?,
+            password: encrypt_data(password, &field_aad(entry_id, PASSWORD_FIELD), key_hierarchy)?,
+            notes: encrypt_data(notes, &field_aad(entry_id, NOTES_FIELD), key_hierarchy)?,
+            totp: encrypt_data(totp, &field_aad(entry_id, TOTP_FIELD), key_hierarchy)?,
+            details: encrypt_details(details, &field_aad(entry_id, DETAILS_FIELD), key_hierarchy)?,
         })
     }
 
-    /// Decrypts the sensitive data using the provided key hierarchy
-    pub fn decrypt(&self, key_hierarchy: &KeyHierarchy)
+    /// Re-encrypts any field written before associated data binding.
+    /// Returns true if something changed and the vault needs to be saved.
+    pub fn rebind(&mut self, entry_id: &Uuid, key_hierarchy: &KeyHierarchy) -> Result<bool, EntryError> {
+        let password = rebind_field(&mut self.password, &field_aad(entry_id, PASSWORD_FIELD), key_hierarchy)?;
+        let notes = rebind_field(&mut self.notes, &field_aad(entry_id, NOTES_FIELD), key_hierarchy)?;
+        let totp = rebind_field(&mut self.totp, &field_aad(entry_id, TOTP_FIELD), key_hierarchy)?;
+        Ok(password || notes || totp)
+    }
+
+    /// Whether these fields already hold the given values, so unchanged saves don't create history
+    pub fn matches(
+        &self,
+        entry_id: &Uuid,
+        password: &Option<String>,
+        notes: &Option<String>,
+        totp: &Option<String>,
+        details: Option<&EntryDetails>,
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<bool, EntryError> {
+        // Empty strings are never encrypted, so they compare equal to a missing field
+        fn same(current: Option<&SecureMemory<String>>, new: &Option<String>) -> bool {
+            let new = new.as_deref().filter(|text| !text.is_empty());
+            current.map(|text| text.as_ref().as_str()) == new
+        }
+
+        let decrypted = self.decrypt(entry_id, key_hierarchy)?;
+        let (current_password, current_notes, current_totp, current_details) = match &decrypted {
+            Some(data) => (data.password.as_ref(), data.notes.as_ref(), data.totp.as_ref(), data.details.as_ref()),
+            None => (None, None, None, None),
+        };
+
+        Ok(same(current_password, password)
+            && same(current_notes, notes)
+            && same(current_totp, totp)
+            && current_details == details)
+    }
+
+    /// Decrypts only the password, for checks that don't need the other fields
+    pub(crate) fn decrypt_password(&self, entry_id: &Uuid, key_hierarchy: &KeyHierarchy)
+                                   -> Result<Option<SecureMemory<String>>, EntryError>
+    {
+        self.password
+            .as_ref()
+            .map(|enc| decrypt_field(enc, &field_aad(entry_id, PASSWORD_FIELD), key_hierarchy))
+            .transpose()
+    }
+
+    /// Decrypts the sensitive data of the entry `entry_id` using the provided key hierarchy
+    pub fn decrypt(&self, entry_id: &Uuid, key_hierarchy: &KeyHierarchy)
                    -> Result<Option<DecryptedSensitiveData>, EntryError>
     {
         // Early return if no sensitive data
-        if self.password.is_none() && self.notes.is_none() {
+        if self.password.is_none() && self.notes.is_none() && self.totp.is_none() && self.details.is_none() {
             return Ok(None);
         }
 
-        // Helper function for decrypting single field
-        fn decrypt_field(encrypted: &EncryptedData, key_hierarchy: &KeyHierarchy) -> Result<SecureMemory<String>, EntryError> {
-            let bytes = key_hierarchy
-                .decrypt_data(encrypted)
-                .map_err(EntryError::DecryptionError)?;
-
-            String::from_utf8(bytes)
-                .map_err(|_| EntryError::DecryptionError(DecryptionFailed))
-                .map(SecureMemory::new)
-        }
-
         // Decrypt each field
         let password = self.password
             .as_ref()
-            .map(|enc| decrypt_field(enc, key_hierarchy))
+            .map(|enc| decrypt_field(enc, &field_aad(entry_id, PASSWORD_FIELD), key_hierarchy))
             .transpose()?;
 
         let notes = self.notes
             .as_ref()
-            .map(|enc| decrypt_field(enc, key_hierarchy))
+            .map(|enc| decrypt_field(enc, &field_aad(entry_id, NOTES_FIELD), key_hierarchy))
+            .transpose()?;
+
+        let totp = self.totp
+            .as_ref()
+            .map(|enc| decrypt_field(enc, &field_aad(entry_id, TOTP_FIELD), key_hierarchy))
+            .transpose()?;
+
+        let details = self.details
+            .as_ref()
+            .map(|enc| {
+                let bytes = SecureMemory::new(
+                    key_hierarchy
+                        .decrypt_data(enc, &field_aad(entry_id, DETAILS_FIELD))
+                        .map_err(EntryError::DecryptionError)?
+                );
+                serde_cbor::from_slice(bytes.as_ref())
+                    .map_err(|_| EntryError::DecryptionError(DecryptionFailed))
+            })
             .transpose()?;
 
-        Ok(Some(DecryptedSensitiveData { password, notes }))
+        Ok(Some(DecryptedSensitiveData { password, notes, totp, details }))
     }
 }
 
@@ -89,6 +210,8 @@ impl EncryptedSensitiveData {
 pub struct DecryptedSensitiveData {
     pub(crate) password: Option<SecureMemory<String>>,
     pub(crate) notes: Option<SecureMemory<String>>,
+    pub(crate) totp: Option<SecureMemory<String>>,
+    pub(crate) details: Option<EntryDetails>,
 }
 
 
@@ -0,0 +1,185 @@
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use url::Url;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::entry::{
+    DEFAULT_TOTP_DIGITS,
+    DEFAULT_TOTP_PERIOD,
+    MAX_TOTP_DIGITS,
+    MAX_TOTP_LENGTH,
+    MAX_TOTP_PERIOD,
+    MIN_TOTP_DIGITS,
+    MIN_TOTP_SECRET_BYTES,
+};
+use crate::error::entry_error::EntryError;
+
+/// HMAC algorithms allowed by RFC 6238
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TotpAlgorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+/// Parsed form of the `totp` field: either an `otpauth://totp/` URI or a bare base32 secret
+#[derive(Debug, Zeroize, ZeroizeOnDrop)]
+pub struct TotpConfig {
+    secret: Vec<u8>,
+    #[zeroize(skip)]
+    algorithm: TotpAlgorithm,
+    digits: u32,
+    period: u64,
+}
+
+/// Current one-time code for an entry
+#[derive(Debug, Serialize, Zeroize, ZeroizeOnDrop)]
+pub struct TotpCode {
+    pub code: String,
+    #[serde(rename = "secondsRemaining")]
+    pub seconds_remaining: u64,
+    pub period: u64,
+}
+
+impl TotpConfig {
+    /// Accepts `otpauth://totp/Label?secret=...&algorithm=...&digits=...&period=...` or a base32 secret
+    pub fn parse(input: &str) -> Result<Self, EntryError> {
+        let input = input.trim();
+        if input.len() > MAX_TOTP_LENGTH {
+            return Err(EntryError::InvalidLength("TOTP".to_string()));
+        }
+
+        let config = if input.to_ascii_lowercase().starts_with("otpauth://") {
+            Self::parse_uri(input)?
+        } else {
+            Self {
+                secret: decode_base32(input)?,
+                algorithm: TotpAlgorithm::Sha1,
+                digits: DEFAULT_TOTP_DIGITS,
+                period: DEFAULT_TOTP_PERIOD,
+            }
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn parse_uri(input: &str) -> Result<Self, EntryError> {
+        let uri = Url::parse(input)
+            .map_err(|_| EntryError::InvalidTotp("Malformed otpauth URI".to_string()))?;
+
+        if !uri.host_str().is_some_and(|kind| kind.eq_ignore_ascii_case("totp")) {
+            return Err(EntryError::InvalidTotp("Only time-based (totp) URIs are supported".to_string()));
+        }
+
+        let mut secret = None;
+        let mut algorithm = TotpAlgorithm::Sha1;
+        let mut digits = DEFAULT_TOTP_DIGITS;
+        let mut period = DEFAULT_TOTP_PERIOD;
+
+        for (key, value) in uri.query_pairs() {
+            match key.to_ascii_lowercase().as_str() {
+                "secret" => secret = Some(decode_base32(&value)?),
+                "algorithm" => {
+                    algorithm = match value.to_ascii_uppercase().as_str() {
+                        "SHA1" => TotpAlgorithm::Sha1,
+                        "SHA256" => TotpAlgorithm::Sha256,
+                        "SHA512" => TotpAlgorithm::Sha512,
+                        other => return Err(EntryError::InvalidTotp(format!("Unsupported algorithm {}", other))),
+                    }
+                }
+                "digits" => {
+                    digits = value.parse()
+                        .map_err(|_| EntryError::InvalidTotp("Digits must be a number".to_string()))?
+                }
+                "period" => {
+                    period = value.parse()
+                        .map_err(|_| EntryError::InvalidTotp("Period must be a number".to_string()))?
+                }
+                // Issuer, label and unknown parameters don't affect the code
+                _ => {}
+            }
+        }
+
+        let secret = secret
+            .ok_or_else(|| EntryError::InvalidTotp("URI has no secret".to_string()))?;
+
+        Ok(Self { secret, algorithm, digits, period })
+    }
+
+    fn validate(&self) -> Result<(), EntryError> {
+        if self.secret.len() < MIN_TOTP_SECRET_BYTES {
+            return Err(EntryError::InvalidTotp("Secret is too short".to_string()));
+        }
+        if !(MIN_TOTP_DIGITS..=MAX_TOTP_DIGITS).contains(&self.digits) {
+            return Err(EntryError::InvalidTotp(
+                format!("Digits must be between {} and {}", MIN_TOTP_DIGITS, MAX_TOTP_DIGITS)
+            ));
+        }
+        if self.period == 0 || self.period > MAX_TOTP_PERIOD {
+            return Err(EntryError::InvalidTotp(
+                format!("Period must be between 1 and {} seconds", MAX_TOTP_PERIOD)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Generates the code for `unix_time` (seconds since the epoch)
+    pub fn generate(&self, unix_time: u64) -> TotpCode {
+        let counter = unix_time / self.period;
+        let hash = match self.algorithm {
+            TotpAlgorithm::Sha1 => hmac_digest::<Hmac<Sha1>>(&self.secret, counter),
+            TotpAlgorithm::Sha256 => hmac_digest::<Hmac<Sha256>>(&self.secret, counter),
+            TotpAlgorithm::Sha512 => hmac_digest::<Hmac<Sha512>>(&self.secret, counter),
+        };
+
+        // Dynamic truncation (RFC 4226 section 5.3)
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            hash[offset] & 0x7f,
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]);
+        let code = u64::from(binary) % 10u64.pow(self.digits);
+
+        TotpCode {
+            code: format!("{:0width$}", code, width = self.digits as usize),
+            seconds_remaining: self.period - unix_time % self.period,
+            period: self.period,
+        }
+    }
+}
+
+fn hmac_digest<M: Mac + hmac::digest::KeyInit>(secret: &[u8], counter: u64) -> Vec<u8> {
+    let mut mac = <M as Mac>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// RFC 4648 base32 without padding requirements; spaces and case are ignored as authenticator apps do
+fn decode_base32(input: &str) -> Result<Vec<u8>, EntryError> {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+    let mut output = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+
+    for c in input.chars().filter(|c| !c.is_whitespace() && *c != '-' && *c != '=') {
+        let value = ALPHABET
+            .iter()
+            .position(|&a| a as char == c.to_ascii_uppercase())
+            .ok_or_else(|| EntryError::InvalidTotp("Secret is not valid base32".to_string()))?;
+
+        buffer = (buffer << 5) | value as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+
+    buffer.zeroize();
+    Ok(output)
+}
@@ -1,7 +1,7 @@
 use serde::Serialize;
 use crate::error::auth_error::AuthError;
 use super::{
-    category_error::CategoryError, crypto_error::CryptoError, entry_error::EntryError, password_generation_error::PasswordGenerationError, serializable_error::SerializableError, vault_error::VaultError
+    breach_error::BreachError, category_error::CategoryError, crypto_error::CryptoError, entry_error::EntryError, import_error::ImportError, kdbx_error::KdbxError, password_generation_error::PasswordGenerationError, serializable_error::SerializableError, vault_error::VaultError
 };
 use thiserror::Error;
 
@@ -36,8 +36,16 @@ pub enum AppError {
     #[error("Password is too long")]
     PasswordTooLong,
 
-    #[error("Password is too weak")]
-    PasswordTooWeak,
+    #[error("Password is too weak: scored {score}, needs {min_score}")]
+    PasswordTooWeak {
+        score: u8,
+        min_score: u8,
+        warning: Option<String>,
+        suggestions: Vec<String>,
+    },
+
+    #[error("Password appears in a known data breach")]
+    PasswordCompromised(u64),
 
     // Password Generation errors
     #[error("Password generation error: {0}")]
@@ -61,6 +69,16 @@ pub enum AppError {
     #[error("Failed to deserialize vault data: {0}")]
     DeserializationFailed(String),
 
+    // Vault file format errors
+    #[error("Invalid vault file header: {0}")]
+    InvalidVaultHeader(String),
+
+    #[error("Vault format version {0} is newer than this version of PawPass supports")]
+    UnsupportedVaultVersion(u16),
+
+    #[error("Failed to migrate vault from format version {0}: {1}")]
+    MigrationFailed(u16, String),
+
     // Backup related errors
     #[error("Failed to create backup at {0}: {1}")]
     BackupCreationFailed(String, String),
@@ -109,6 +127,18 @@ pub enum AppError {
     // Vault errors
     #[error(transparent)]
     VaultError(#[from] VaultError),
+
+    // Import errors
+    #[error(transparent)]
+    ImportError(#[from] ImportError),
+
+    // KeePass errors
+    #[error(transparent)]
+    KdbxError(#[from] KdbxError),
+
+    // Breached password list errors
+    #[error(transparent)]
+    BreachError(#[from] BreachError),
 }
 
 // Only keep necessary From implementations that need custom conversion logic
@@ -163,6 +193,36 @@ impl Serialize for AppError {
                         message: "Operation failed".to_string(),
                         details: Some(details.to_string()),
                     },
+                    AuthError::TooManyAttempts { retry_after_seconds } => SerializableError::Auth {
+                        code: "AUTH006".to_string(),
+                        message: "Too many failed attempts; please wait before trying again".to_string(),
+                        details: Some(retry_after_seconds.to_string()),
+                    },
+                    AuthError::VaultWiped => SerializableError::Auth {
+                        code: "AUTH007".to_string(),
+                        message: "Too many failed attempts; the vault has been wiped".to_string(),
+                        details: None,
+                    },
+                    AuthError::InvalidUnlockPolicy(details) => SerializableError::Auth {
+                        code: "AUTH008".to_string(),
+                        message: "Invalid unlock policy".to_string(),
+                        details: Some(details.to_string()),
+                    },
+                    AuthError::InvalidPin { remaining_attempts } => SerializableError::Auth {
+                        code: "AUTH009".to_string(),
+                        message: "Incorrect PIN".to_string(),
+                        details: Some(remaining_attempts.to_string()),
+                    },
+                    AuthError::QuickUnlockUnavailable => SerializableError::Auth {
+                        code: "AUTH010".to_string(),
+                        message: "Quick unlock is not available; use the master password".to_string(),
+                        details: None,
+                    },
+                    AuthError::InvalidPinFormat(details) => SerializableError::Auth {
+                        code: "AUTH011".to_string(),
+                        message: "Invalid PIN".to_string(),
+                        details: Some(details.to_string()),
+                    },
                     _ => SerializableError::Auth {
                         code: "AUTH999".to_string(),
                         message: auth_err.to_string(),
@@ -259,6 +319,42 @@ impl Serialize for AppError {
                         entry_id: Some(id.to_string()),
                         field_name: None,
                     },
+                    EntryError::HistoryNotFound(_) => SerializableError::Entry {
+                        code: "ENTRY004".to_string(),
+                        message: "Password history version not found".to_string(),
+                        entry_id: None,
+                        field_name: Some("history".to_string()),
+                    },
+                    EntryError::InvalidTotp(details) => SerializableError::Entry {
+                        code: "ENTRY005".to_string(),
+                        message: format!("Invalid TOTP: {}", details),
+                        entry_id: None,
+                        field_name: Some("totp".to_string()),
+                    },
+                    EntryError::TotpNotConfigured => SerializableError::Entry {
+                        code: "ENTRY006".to_string(),
+                        message: "Entry has no TOTP secret".to_string(),
+                        entry_id: None,
+                        field_name: Some("totp".to_string()),
+                    },
+                    EntryError::InvalidCustomField(details) => SerializableError::Entry {
+                        code: "ENTRY007".to_string(),
+                        message: format!("Invalid custom field: {}", details),
+                        entry_id: None,
+                        field_name: Some("customFields".to_string()),
+                    },
+                    EntryError::InvalidDetails(details) => SerializableError::Entry {
+                        code: "ENTRY008".to_string(),
+                        message: format!("Invalid entry details: {}", details),
+                        entry_id: None,
+                        field_name: Some("details".to_string()),
+                    },
+                    EntryError::AttachmentNotFound(_) => SerializableError::Entry {
+                        code: "ENTRY009".to_string(),
+                        message: "Attachment not found".to_string(),
+                        entry_id: None,
+                        field_name: Some("attachments".to_string()),
+                    },
                     _ => SerializableError::Entry {
                         code: "ENTRY999".to_string(),
                         message: entry_err.to_string(),
@@ -311,11 +407,19 @@ impl Serialize for AppError {
                 length: None,
                 details: None,
             },
-            AppError::PasswordTooWeak => SerializableError::PasswordGeneration {
+            AppError::PasswordTooWeak { score, min_score, warning, suggestions } => SerializableError::PasswordStrength {
                 code: "PWD004".to_string(),
                 message: "Password does not meet strength requirements".to_string(),
+                score: *score,
+                min_score: *min_score,
+                warning: warning.clone(),
+                suggestions: suggestions.clone(),
+            },
+            AppError::PasswordCompromised(occurrences) => SerializableError::PasswordGeneration {
+                code: "PWD005".to_string(),
+                message: "Password appears in a known data breach".to_string(),
                 length: None,
-                details: None,
+                details: Some(format!("Seen {} times in breached password lists", occurrences)),
             },
             AppError::PasswordsDoNotMatch => SerializableError::Validation {
                 code: "VAL001".to_string(),
@@ -333,6 +437,12 @@ impl Serialize for AppError {
                         path: None,
                         details: None,
                     },
+                    VaultError::VaultTampered => SerializableError::Vault {
+                        code: "VAULT007".to_string(),
+                        message: "Vault file has been tampered with".to_string(),
+                        path: None,
+                        details: Some("Restore the newest verified backup to recover".to_string()),
+                    },
                     _ => SerializableError::Vault {
                         code: "VAULT001".to_string(),
                         message: "Vault operation failed".to_string(),
@@ -382,6 +492,21 @@ impl Serialize for AppError {
                 message: "Failed to deserialize data".to_string(),
                 details: Some(details.clone()),
             },
+            AppError::InvalidVaultHeader(details) => SerializableError::Data {
+                code: "DATA004".to_string(),
+                message: "Vault file header is invalid".to_string(),
+                details: Some(details.clone()),
+            },
+            AppError::UnsupportedVaultVersion(version) => SerializableError::Data {
+                code: "DATA005".to_string(),
+                message: "Vault was created by a newer version of PawPass".to_string(),
+                details: Some(format!("Format version {}", version)),
+            },
+            AppError::MigrationFailed(version, details) => SerializableError::Data {
+                code: "DATA006".to_string(),
+                message: "Failed to upgrade vault to the current format".to_string(),
+                details: Some(format!("From version {}: {}", version, details)),
+            },
 
             // Backup Related Errors
             AppError::BackupCreationFailed(path, details) => SerializableError::Vault {
@@ -403,6 +528,53 @@ impl Serialize for AppError {
                 details: None,
             },
 
+            // Import Errors
+            AppError::ImportError(import_err) => {
+                let code = match import_err {
+                    ImportError::UnrecognizedFormat => "IMPORT001",
+                    ImportError::MissingColumn(_) => "IMPORT002",
+                    ImportError::Malformed(_) => "IMPORT003",
+                    ImportError::EncryptedExport => "IMPORT004",
+                    ImportError::TooLarge(_) => "IMPORT005",
+                    ImportError::IncorrectPassword => "IMPORT006",
+                    ImportError::UnsupportedVersion(_) => "IMPORT007",
+                };
+                SerializableError::Data {
+                    code: code.to_string(),
+                    message: "Import failed".to_string(),
+                    details: Some(import_err.to_string()),
+                }
+            },
+
+            // KeePass Errors
+            AppError::KdbxError(kdbx_err) => {
+                let code = match kdbx_err {
+                    KdbxError::IncorrectPassword => "KDBX001",
+                    KdbxError::UnsupportedVersion => "KDBX002",
+                    KdbxError::Corrupted(_) => "KDBX003",
+                    KdbxError::WriteFailed(_) => "KDBX004",
+                };
+                SerializableError::Data {
+                    code: code.to_string(),
+                    message: "KeePass database error".to_string(),
+                    details: Some(kdbx_err.to_string()),
+                }
+            },
+
+            // Breached Password List Errors
+            AppError::BreachError(breach_err) => {
+                let code = match breach_err {
+                    BreachError::NotFound(_) => "BREACH001",
+                    BreachError::Malformed(_) => "BREACH002",
+                    BreachError::ReadFailed(_) => "BREACH003",
+                };
+                SerializableError::Data {
+                    code: code.to_string(),
+                    message: "Breached password check failed".to_string(),
+                    details: Some(breach_err.to_string()),
+                }
+            },
+
             // Other File System Errors
             AppError::InsufficientPermissions(path) => SerializableError::Io {
                 code: "IO003".to_string(),
@@ -24,6 +24,26 @@ pub enum AuthError {
 
     #[error("Operation error: {0}")]
     OperationError(String),
+
+    // Unlock rate limiting errors
+    #[error("Too many failed attempts; try again in {retry_after_seconds} seconds")]
+    TooManyAttempts { retry_after_seconds: u64 },
+
+    #[error("Too many failed attempts; the vault has been wiped")]
+    VaultWiped,
+
+    #[error("Invalid unlock policy: {0}")]
+    InvalidUnlockPolicy(String),
+
+    // Quick unlock errors
+    #[error("Incorrect PIN; {remaining_attempts} attempts remaining")]
+    InvalidPin { remaining_attempts: u32 },
+
+    #[error("Quick unlock is not available; use the master password")]
+    QuickUnlockUnavailable,
+
+    #[error("Invalid PIN: {0}")]
+    InvalidPinFormat(String),
 }
 
 impl From<CryptoError> for AuthError {
@@ -0,0 +1,14 @@
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+pub enum BreachError {
+    #[error("Breached password list not found: {0}")]
+    NotFound(String),
+
+    #[error("Breached password list is malformed: {0}")]
+    Malformed(String),
+
+    #[error("Failed to read the breached password list: {0}")]
+    ReadFailed(String),
+}
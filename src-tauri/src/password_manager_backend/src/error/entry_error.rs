@@ -30,6 +30,9 @@ pub enum EntryError {
     #[error("{0} exceeds maximum length")]
     InvalidLength(String),
 
+    #[error("Invalid TOTP: {0}")]
+    InvalidTotp(String),
+
     // Entry operation errors
     #[error("Entry not found with ID: {0}")]
     NotFound(Uuid),
@@ -37,6 +40,21 @@ pub enum EntryError {
     #[error("Entry with this title already exists")]
     DuplicateEntry,
 
+    #[error("History version not found with ID: {0}")]
+    HistoryNotFound(Uuid),
+
+    #[error("Entry has no TOTP secret")]
+    TotpNotConfigured,
+
+    #[error("Invalid custom field: {0}")]
+    InvalidCustomField(String),
+
+    #[error("Invalid entry details: {0}")]
+    InvalidDetails(String),
+
+    #[error("Attachment not found with ID: {0}")]
+    AttachmentNotFound(Uuid),
+
     // Other errors
     #[error("Validation error: {0}")]
     ValidationError(String),
@@ -0,0 +1,26 @@
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+pub enum ImportError {
+    #[error("Could not recognize the export format")]
+    UnrecognizedFormat,
+
+    #[error("Export is missing the required column: {0}")]
+    MissingColumn(String),
+
+    #[error("Export file is malformed: {0}")]
+    Malformed(String),
+
+    #[error("Encrypted exports are not supported; export unencrypted and delete the file afterwards")]
+    EncryptedExport,
+
+    #[error("Export is too large: {0}")]
+    TooLarge(String),
+
+    #[error("Incorrect password, or the export file has been modified")]
+    IncorrectPassword,
+
+    #[error("Export format version {0} is newer than this version of PawPass supports")]
+    UnsupportedVersion(u16),
+}
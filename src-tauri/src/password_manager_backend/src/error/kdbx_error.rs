@@ -0,0 +1,17 @@
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+pub enum KdbxError {
+    #[error("Incorrect password for the KeePass database")]
+    IncorrectPassword,
+
+    #[error("This KeePass database version is not supported")]
+    UnsupportedVersion,
+
+    #[error("KeePass database is corrupted: {0}")]
+    Corrupted(String),
+
+    #[error("Failed to write KeePass database: {0}")]
+    WriteFailed(String),
+}
@@ -5,4 +5,6 @@ pub mod auth_error;
 pub mod password_generation_error;
 pub mod vault_error;
 pub mod app_error;
-pub mod serializable_error;
\ No newline at end of file
+pub mod serializable_error;
+pub mod import_error;pub mod kdbx_error;
+pub mod breach_error;
\ No newline at end of file
@@ -1,6 +1,7 @@
 use serde::Serialize;
 use thiserror::Error;
-use crate::_vault::{ABSOLUTE_MAX_LENGTH, ABSOLUTE_MIN_LENGTH};
+use uuid::Uuid;
+use crate::_vault::{ABSOLUTE_MAX_LENGTH, ABSOLUTE_MIN_LENGTH, MAX_PASSPHRASE_SEPARATOR_LENGTH, MAX_PASSPHRASE_WORDS, MIN_PASSPHRASE_WORDS};
 
 #[derive(Debug, Error, Serialize)]
 pub enum PasswordGenerationError {
@@ -24,4 +25,19 @@ pub enum PasswordGenerationError {
 
     #[error("System error during password generation: {0}")]
     SystemError(String),
+
+    #[error("Passphrase word count {0} must be between {MIN_PASSPHRASE_WORDS} and {MAX_PASSPHRASE_WORDS}")]
+    WordCountOutOfRange(usize),
+
+    #[error("Passphrase separator {0:?} must be at most {MAX_PASSPHRASE_SEPARATOR_LENGTH} characters without letters")]
+    InvalidSeparator(String),
+
+    #[error("Invalid password rules: {0}")]
+    InvalidRules(String),
+
+    #[error("No password meeting the rules was found: {0}")]
+    RulesUnsatisfiable(String),
+
+    #[error("Generator profile not found: {0}")]
+    ProfileNotFound(Uuid),
 }
\ No newline at end of file
@@ -50,6 +50,17 @@ pub enum SerializableError {
         details: Option<String>,
     },
 
+    // Password Strength Errors, with the estimator's feedback
+    PasswordStrength {
+        code: String,
+        message: String,
+        score: u8,
+        min_score: u8,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        warning: Option<String>,
+        suggestions: Vec<String>,
+    },
+
     // Vault Errors
     Vault {
         code: String,
@@ -25,6 +25,9 @@ pub enum VaultError {
     #[error("Vault not found at specified path")]
     VaultNotFound,
 
+    #[error("Vault file failed its integrity check and may have been tampered with")]
+    VaultTampered,
+
     // Component errors
     #[error("Authentication error: {0}")]
     AuthError(#[from] AuthError),
@@ -65,6 +68,9 @@ pub enum VaultError {
     #[error("Backup error: {0}")]
     BackupError(String),
     
+    #[error("Merge error: {0}")]
+    MergeError(String),
+    
     #[error("Validation error: {0}")]
     ValidationError(String),
     
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::entry::custom_field::{CustomFieldData, CustomFieldType};
+use crate::entry::kind::{EntryDetails, EntryKind};
+use crate::error::import_error::ImportError;
+use super::{ImportedRow, ParsedRow, RowOutcome};
+
+// Item and field type codes used by Bitwarden's JSON export
+const TYPE_LOGIN: u8 = 1;
+const TYPE_SECURE_NOTE: u8 = 2;
+const TYPE_CARD: u8 = 3;
+const TYPE_IDENTITY: u8 = 4;
+const TYPE_SSH_KEY: u8 = 5;
+const FIELD_HIDDEN: u8 = 1;
+const FIELD_LINKED: u8 = 3;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Export {
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    folders: Vec<Folder>,
+    #[serde(default)]
+    items: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+struct Folder {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    #[serde(rename = "type")]
+    item_type: u8,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    favorite: bool,
+    #[serde(default)]
+    folder_id: Option<String>,
+    #[serde(default)]
+    fields: Vec<Field>,
+    #[serde(default)]
+    login: Option<Login>,
+    #[serde(default)]
+    card: Option<Card>,
+    #[serde(default)]
+    identity: Option<Identity>,
+    #[serde(default)]
+    ssh_key: Option<SshKey>,
+}
+
+#[derive(Deserialize)]
+struct Field {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(rename = "type", default)]
+    field_type: u8,
+}
+
+#[derive(Deserialize)]
+struct Login {
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    #[serde(default)]
+    totp: Option<String>,
+    #[serde(default)]
+    uris: Vec<LoginUri>,
+}
+
+#[derive(Deserialize)]
+struct LoginUri {
+    #[serde(default)]
+    uri: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Card {
+    cardholder_name: Option<String>,
+    number: Option<String>,
+    exp_month: Option<String>,
+    exp_year: Option<String>,
+    code: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Identity {
+    first_name: Option<String>,
+    middle_name: Option<String>,
+    last_name: Option<String>,
+    email: Option<String>,
+    phone: Option<String>,
+    address1: Option<String>,
+    address2: Option<String>,
+    address3: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    postal_code: Option<String>,
+    country: Option<String>,
+    ssn: Option<String>,
+    passport_number: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SshKey {
+    private_key: Option<String>,
+    public_key: Option<String>,
+}
+
+// Joins the non-empty parts, or None if there are none
+fn join(parts: &[&Option<String>], separator: &str) -> Option<String> {
+    let parts: Vec<&str> = parts
+        .iter()
+        .filter_map(|part| part.as_deref().map(str::trim))
+        .filter(|part| !part.is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(separator))
+}
+
+/// Reads an unencrypted Bitwarden JSON export
+pub(crate) fn parse(content: &str) -> Result<Vec<ParsedRow>, ImportError> {
+    let export: Export = serde_json::from_str(content.trim_start_matches('\u{feff}'))
+        .map_err(|e| ImportError::Malformed(e.to_string()))?;
+    if export.encrypted {
+        return Err(ImportError::EncryptedExport);
+    }
+
+    let folders: HashMap<String, String> = export.folders
+        .into_iter()
+        .map(|folder| (folder.id, folder.name))
+        .collect();
+
+    Ok(export.items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| ParsedRow {
+            row: index + 1,
+            title: item.name.clone().filter(|name| !name.is_empty()),
+            outcome: convert(item, &folders),
+        })
+        .collect())
+}
+
+fn convert(item: Item, folders: &HashMap<String, String>) -> RowOutcome {
+    let mut row = ImportedRow {
+        title: item.name.unwrap_or_default(),
+        notes: item.notes.unwrap_or_default(),
+        favorite: item.favorite,
+        category: item.folder_id
+            .and_then(|id| folders.get(&id).cloned())
+            .unwrap_or_default(),
+        custom_fields: item.fields
+            .into_iter()
+            .filter(|field| field.field_type != FIELD_LINKED)
+            .map(|field| CustomFieldData {
+                name: field.name.unwrap_or_default(),
+                field_type: if field.field_type == FIELD_HIDDEN { CustomFieldType::Hidden } else { CustomFieldType::Text },
+                value: field.value.unwrap_or_default(),
+                searchable: false,
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    match item.item_type {
+        TYPE_LOGIN => {
+            if let Some(login) = item.login {
+                row.username = login.username.unwrap_or_default();
+                row.password = login.password.unwrap_or_default();
+                row.totp = login.totp.unwrap_or_default();
+                row.url = login.uris
+                    .into_iter()
+                    .find_map(|uri| uri.uri)
+                    .unwrap_or_default();
+            }
+        }
+        TYPE_SECURE_NOTE => row.kind = EntryKind::SecureNote,
+        TYPE_CARD => {
+            let Some(card) = item.card else {
+                return RowOutcome::Invalid("Card item has no card data".to_string());
+            };
+            let (Some(expiry_month), Some(expiry_year)) = (
+                card.exp_month.as_deref().and_then(|month| month.trim().parse().ok()),
+                card.exp_year.as_deref().and_then(|year| year.trim().parse().ok()),
+            ) else {
+                return RowOutcome::Invalid("Card is missing a valid expiry date".to_string());
+            };
+            row.kind = EntryKind::PaymentCard;
+            row.details = Some(EntryDetails::PaymentCard {
+                cardholder_name: card.cardholder_name,
+                number: card.number.unwrap_or_default(),
+                expiry_month,
+                expiry_year,
+                security_code: card.code,
+                pin: None,
+            });
+        }
+        TYPE_IDENTITY => {
+            let Some(identity) = item.identity else {
+                return RowOutcome::Invalid("Identity item has no identity data".to_string());
+            };
+            row.kind = EntryKind::Identity;
+            row.details = Some(EntryDetails::Identity {
+                full_name: join(&[&identity.first_name, &identity.middle_name, &identity.last_name], " "),
+                email: identity.email,
+                phone: identity.phone,
+                address: join(&[
+                    &identity.address1, &identity.address2, &identity.address3,
+                    &identity.city, &identity.state, &identity.postal_code, &identity.country,
+                ], ", "),
+                date_of_birth: None,
+                national_id: identity.ssn,
+                passport_number: identity.passport_number,
+            });
+        }
+        TYPE_SSH_KEY => {
+            let Some(ssh_key) = item.ssh_key else {
+                return RowOutcome::Invalid("SSH key item has no key data".to_string());
+            };
+            row.kind = EntryKind::SshKey;
+            row.details = Some(EntryDetails::SshKey {
+                private_key: ssh_key.private_key.unwrap_or_default(),
+                public_key: ssh_key.public_key,
+                passphrase: None,
+            });
+        }
+        other => return RowOutcome::Skip(format!("Unknown item type {}", other)),
+    }
+
+    RowOutcome::Entry(Box::new(row))
+}
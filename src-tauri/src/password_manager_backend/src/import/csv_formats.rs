@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use csv::{ReaderBuilder, StringRecord};
+use crate::entry::custom_field::{CustomFieldData, CustomFieldType};
+use crate::entry::kind::EntryKind;
+use crate::error::import_error::ImportError;
+use super::{ImportFormat, ImportedRow, ParsedRow, RowOutcome};
+
+// LastPass stores secure notes as items with this placeholder URL
+const LASTPASS_NOTE_URL: &str = "http://sn";
+
+// Turns one CSV record into an import outcome
+type RowParser = fn(&Columns, &StringRecord) -> RowOutcome;
+
+/// Header lookup that tolerates different casing and the aliases used across versions of an exporter
+struct Columns(HashMap<String, usize>);
+
+impl Columns {
+    fn new(headers: &StringRecord) -> Self {
+        Self(
+            headers
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (name.trim().trim_start_matches('\u{feff}').to_lowercase(), index))
+                .collect()
+        )
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    fn require(&self, aliases: &[&str]) -> Result<(), ImportError> {
+        if aliases.iter().any(|alias| self.has(alias)) {
+            Ok(())
+        } else {
+            Err(ImportError::MissingColumn(aliases[0].to_string()))
+        }
+    }
+
+    /// Value of the first alias present in the header, or an empty string
+    fn get(&self, record: &StringRecord, aliases: &[&str]) -> String {
+        aliases
+            .iter()
+            .find_map(|alias| self.0.get(*alias))
+            .and_then(|&index| record.get(index))
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    }
+}
+
+fn headers(content: &str) -> Result<Columns, ImportError> {
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(content.as_bytes());
+    let headers = reader.headers().map_err(|e| ImportError::Malformed(e.to_string()))?;
+    Ok(Columns::new(headers))
+}
+
+/// Identifies the exporter from the columns in the header row
+pub(crate) fn detect(content: &str) -> Result<ImportFormat, ImportError> {
+    let columns = headers(content)?;
+
+    if columns.has("login_uri") {
+        Ok(ImportFormat::BitwardenCsv)
+    } else if columns.has("grouping") || columns.has("extra") {
+        Ok(ImportFormat::LastPassCsv)
+    } else if columns.has("otpauth") || columns.has("archived") {
+        Ok(ImportFormat::OnePasswordCsv)
+    } else if (columns.has("group") && columns.has("title")) || columns.has("login name") {
+        Ok(ImportFormat::KeePassCsv)
+    } else if columns.has("name") && columns.has("url") && columns.has("password") {
+        Ok(ImportFormat::ChromeCsv)
+    } else {
+        Err(ImportError::UnrecognizedFormat)
+    }
+}
+
+/// Reads every data row of a CSV export in `format`
+pub(crate) fn parse(content: &str, format: ImportFormat) -> Result<Vec<ParsedRow>, ImportError> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(content.as_bytes());
+    let columns = Columns::new(reader.headers().map_err(|e| ImportError::Malformed(e.to_string()))?);
+
+    let (title_column, parse_row): (&[&str], RowParser) = match format {
+        ImportFormat::BitwardenCsv => (&["name"], bitwarden),
+        ImportFormat::OnePasswordCsv => (&["title"], one_password),
+        ImportFormat::KeePassCsv => (&["title", "account"], keepass),
+        ImportFormat::LastPassCsv => (&["name"], lastpass),
+        ImportFormat::ChromeCsv => (&["name"], chrome),
+        ImportFormat::BitwardenJson | ImportFormat::Kdbx | ImportFormat::PawPass => return Err(ImportError::UnrecognizedFormat),
+    };
+    columns.require(title_column)?;
+
+    let mut rows = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let row = index + 1;
+        let outcome = match &record {
+            Ok(record) => parse_row(&columns, record),
+            Err(e) => RowOutcome::Invalid(format!("Could not read row: {}", e)),
+        };
+        let title = record
+            .ok()
+            .map(|record| columns.get(&record, title_column))
+            .filter(|title| !title.is_empty());
+        rows.push(ParsedRow { row, title, outcome });
+    }
+    Ok(rows)
+}
+
+fn is_true(value: &str) -> bool {
+    matches!(value.to_lowercase().as_str(), "1" | "true" | "yes")
+}
+
+// Folder paths such as `Root/Work/Email` become a category named after the innermost folder
+fn folder_category(path: &str, separators: &[char]) -> String {
+    path.rsplit(separators)
+        .find(|segment| !segment.trim().is_empty())
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+// Columns: folder,favorite,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp
+fn bitwarden(columns: &Columns, record: &StringRecord) -> RowOutcome {
+    let kind = match columns.get(record, &["type"]).to_lowercase().as_str() {
+        "login" | "" => EntryKind::Login,
+        "note" => EntryKind::SecureNote,
+        other => return RowOutcome::Skip(format!("Item type '{}' is not in CSV exports; use the JSON export", other)),
+    };
+
+    // Custom fields are exported as `name: value` lines
+    let custom_fields = columns
+        .get(record, &["fields"])
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| CustomFieldData {
+            name: name.trim().to_string(),
+            field_type: CustomFieldType::Text,
+            value: value.trim().to_string(),
+            searchable: false,
+        })
+        .collect();
+
+    RowOutcome::Entry(Box::new(ImportedRow {
+        title: columns.get(record, &["name"]),
+        username: columns.get(record, &["login_username"]),
+        password: columns.get(record, &["login_password"]),
+        // Multiple URIs are comma separated; the first one is the main site
+        url: columns.get(record, &["login_uri"]).split(',').next().unwrap_or_default().to_string(),
+        notes: columns.get(record, &["notes"]),
+        totp: columns.get(record, &["login_totp"]),
+        category: columns.get(record, &["folder"]),
+        favorite: is_true(&columns.get(record, &["favorite"])),
+        kind,
+        custom_fields,
+        ..Default::default()
+    }))
+}
+
+// Columns: Title,Url,Username,Password,OTPAuth,Favorite,Archived,Tags,Notes
+fn one_password(columns: &Columns, record: &StringRecord) -> RowOutcome {
+    if is_true(&columns.get(record, &["archived"])) {
+        return RowOutcome::Skip("Item is archived".to_string());
+    }
+
+    let tags = columns.get(record, &["tags"]);
+    RowOutcome::Entry(Box::new(ImportedRow {
+        title: columns.get(record, &["title"]),
+        username: columns.get(record, &["username"]),
+        password: columns.get(record, &["password"]),
+        url: columns.get(record, &["url", "website"]),
+        notes: columns.get(record, &["notes", "notesplain"]),
+        totp: columns.get(record, &["otpauth"]),
+        // Only the first tag can become the category
+        category: tags.split([',', ';']).next().unwrap_or_default().trim().to_string(),
+        favorite: is_true(&columns.get(record, &["favorite"])),
+        ..Default::default()
+    }))
+}
+
+// KeePassXC: "Group","Title","Username","Password","URL","Notes","TOTP",...
+// KeePass 2: "Account","Login Name","Password","Web Site","Comments"
+fn keepass(columns: &Columns, record: &StringRecord) -> RowOutcome {
+    let group = columns.get(record, &["group"]);
+    if group.split('/').any(|segment| segment.trim().eq_ignore_ascii_case("Recycle Bin")) {
+        return RowOutcome::Skip("Item is in the recycle bin".to_string());
+    }
+
+    // The root group holds every entry, so it isn't a useful category
+    let category = if group.contains('/') { folder_category(&group, &['/']) } else { String::new() };
+
+    RowOutcome::Entry(Box::new(ImportedRow {
+        title: columns.get(record, &["title", "account"]),
+        username: columns.get(record, &["username", "login name"]),
+        password: columns.get(record, &["password"]),
+        url: columns.get(record, &["url", "web site"]),
+        notes: columns.get(record, &["notes", "comments"]),
+        totp: columns.get(record, &["totp"]),
+        category,
+        ..Default::default()
+    }))
+}
+
+// Columns: url,username,password,totp,extra,name,grouping,fav
+fn lastpass(columns: &Columns, record: &StringRecord) -> RowOutcome {
+    let url = columns.get(record, &["url"]);
+    let kind = if url.eq_ignore_ascii_case(LASTPASS_NOTE_URL) {
+        EntryKind::SecureNote
+    } else {
+        EntryKind::Login
+    };
+
+    let mut row = ImportedRow {
+        title: columns.get(record, &["name"]),
+        notes: columns.get(record, &["extra"]),
+        category: folder_category(&columns.get(record, &["grouping"]), &['\\', '/']),
+        favorite: is_true(&columns.get(record, &["fav"])),
+        kind,
+        ..Default::default()
+    };
+    if kind == EntryKind::Login {
+        row.url = url;
+        row.username = columns.get(record, &["username"]);
+        row.password = columns.get(record, &["password"]);
+        row.totp = columns.get(record, &["totp"]);
+    }
+    RowOutcome::Entry(Box::new(row))
+}
+
+// Columns: name,url,username,password,note
+fn chrome(columns: &Columns, record: &StringRecord) -> RowOutcome {
+    RowOutcome::Entry(Box::new(ImportedRow {
+        title: columns.get(record, &["name"]),
+        username: columns.get(record, &["username"]),
+        password: columns.get(record, &["password"]),
+        url: columns.get(record, &["url"]),
+        notes: columns.get(record, &["note", "notes"]),
+        ..Default::default()
+    }))
+}
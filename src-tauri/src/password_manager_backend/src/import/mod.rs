@@ -0,0 +1,378 @@
+pub mod csv_formats;
+pub mod bitwarden_json;
+
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::crypto::KeyHierarchy;
+use crate::entry::collection::EntryCollection;
+use crate::entry::custom_field::CustomFieldData;
+use crate::entry::kind::{EntryDetails, EntryKind};
+use crate::entry::overview::EntryData;
+use crate::entry::MAX_TITLE_LENGTH;
+use crate::error::entry_error::EntryError;
+use crate::error::app_error::AppError;
+use crate::error::import_error::ImportError;
+use crate::kdbx;
+use crate::_vault::portable::PortableVault;
+
+// Import limits
+pub const MAX_IMPORT_FILE_SIZE: usize = 50 * 1024 * 1024; // 50 MiB
+pub const MAX_IMPORT_ROWS: usize = 20_000;
+
+/// Export formats PawPass can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportFormat {
+    BitwardenCsv,
+    BitwardenJson,
+    OnePasswordCsv,
+    KeePassCsv,
+    LastPassCsv,
+    ChromeCsv,
+    // KeePass databases are read by the `kdbx` module since they need a password
+    Kdbx,
+    // Password-protected archives written by `PortableVault::seal`
+    PawPass,
+}
+
+impl ImportFormat {
+    /// Guesses the format from the file content: JSON by its shape, CSV by its header row
+    pub fn detect(content: &str) -> Result<Self, ImportError> {
+        let content = content.trim_start_matches('\u{feff}').trim_start();
+        if content.starts_with('{') {
+            return Ok(ImportFormat::BitwardenJson);
+        }
+        csv_formats::detect(content)
+    }
+}
+
+/// A row the importer could not use, with the reason shown to the user
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportIssue {
+    // 1-based position in the export, not counting the header
+    pub row: usize,
+    pub title: Option<String>,
+    pub reason: String,
+}
+
+/// Summary of an import; a dry run returns the same report without touching the vault
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub format: ImportFormat,
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
+    #[serde(rename = "totalRows")]
+    pub total_rows: usize,
+    pub imported: usize,
+    #[serde(rename = "newCategories")]
+    pub new_categories: Vec<String>,
+    pub skipped: Vec<ImportIssue>,
+    pub invalid: Vec<ImportIssue>,
+}
+
+/// What a format parser made of one row of the export
+pub(crate) enum RowOutcome {
+    Entry(Box<ImportedRow>),
+    // Rows that already are PawPass entries and need no normalizing
+    Data(Box<EntryData>),
+    // Rows that are deliberately left out, e.g. empty rows or folders
+    Skip(String),
+    // Rows that should have been imported but could not be read
+    Invalid(String),
+}
+
+pub(crate) struct ParsedRow {
+    pub row: usize,
+    pub title: Option<String>,
+    pub outcome: RowOutcome,
+}
+
+/// Fields a parser extracted from a row, before they are normalized into `EntryData`
+#[derive(Default)]
+pub(crate) struct ImportedRow {
+    pub title: String,
+    pub username: String,
+    pub password: String,
+    pub url: String,
+    pub notes: String,
+    pub totp: String,
+    pub category: String,
+    pub favorite: bool,
+    pub kind: EntryKind,
+    pub details: Option<EntryDetails>,
+    pub custom_fields: Vec<CustomFieldData>,
+}
+
+impl ImportedRow {
+    fn is_empty(&self) -> bool {
+        [&self.title, &self.username, &self.password, &self.url, &self.notes]
+            .iter()
+            .all(|field| field.trim().is_empty())
+            && self.details.is_none()
+            && self.custom_fields.is_empty()
+    }
+
+    fn into_entry_data(self) -> EntryData {
+        fn optional(value: String) -> Option<String> {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        }
+
+        // Exports often store bare domains; PawPass requires a scheme
+        let url = optional(self.url).map(|url| {
+            if url.contains("://") { url } else { format!("https://{}", url) }
+        });
+
+        // Some managers allow untitled items; fall back to something recognizable
+        let mut title = self.title.trim().to_string();
+        if title.is_empty() {
+            title = url
+                .as_deref()
+                .and_then(|url| url::Url::parse(url).ok())
+                .and_then(|url| url.host_str().map(str::to_string))
+                .or_else(|| optional(self.username.clone()))
+                .unwrap_or_else(|| "Untitled".to_string());
+        }
+        if title.len() > MAX_TITLE_LENGTH {
+            let mut end = MAX_TITLE_LENGTH;
+            while !title.is_char_boundary(end) {
+                end -= 1;
+            }
+            title.truncate(end);
+        }
+
+        EntryData {
+            title,
+            username: optional(self.username),
+            password: optional(self.password),
+            url,
+            notes: optional(self.notes),
+            category_name: optional(self.category),
+            favorite: self.favorite,
+            totp: optional(self.totp),
+            custom_fields: self.custom_fields,
+            kind: self.kind,
+            details: self.details,
+        }
+    }
+}
+
+/// A validated import, ready to be committed or reported as a dry run
+pub struct ImportPlan {
+    report: ImportReport,
+    entries: Vec<EntryData>,
+    // New categories that no imported entry uses, created alongside the entries
+    empty_categories: Vec<String>,
+}
+
+/// What a committed import added, so it can be undone if the vault fails to save
+pub struct ImportBatch {
+    pub entry_ids: Vec<Uuid>,
+    pub category_ids: Vec<Uuid>,
+}
+
+impl ImportBatch {
+    /// Removes everything the import added
+    pub fn revert(&self, collection: &mut EntryCollection) {
+        for id in &self.entry_ids {
+            let _ = collection.delete_entry(id);
+        }
+        for id in &self.category_ids {
+            let _ = collection.categories.delete_category(id, &mut collection.entries);
+        }
+    }
+}
+
+impl ImportPlan {
+    /// Parses `content`, validates each row with `EntryData::validate` and drops duplicates of
+    /// entries already in `collection`. Nothing is written.
+    pub fn prepare(
+        content: &str,
+        format: Option<ImportFormat>,
+        collection: &EntryCollection,
+    ) -> Result<Self, ImportError> {
+        if content.len() > MAX_IMPORT_FILE_SIZE {
+            return Err(ImportError::TooLarge(format!("files are limited to {} MiB", MAX_IMPORT_FILE_SIZE / 1024 / 1024)));
+        }
+
+        let format = match format {
+            Some(format) => format,
+            None => ImportFormat::detect(content)?,
+        };
+        let rows = match format {
+            ImportFormat::BitwardenJson => bitwarden_json::parse(content)?,
+            csv_format => csv_formats::parse(content, csv_format)?,
+        };
+        Self::from_rows(format, rows, collection)
+    }
+
+    /// Decrypts a KeePass database and plans its import like any other export
+    pub fn prepare_kdbx(
+        data: &[u8],
+        password: &str,
+        collection: &EntryCollection,
+    ) -> Result<Self, AppError> {
+        if data.len() > MAX_IMPORT_FILE_SIZE {
+            return Err(ImportError::TooLarge(format!("files are limited to {} MiB", MAX_IMPORT_FILE_SIZE / 1024 / 1024)).into());
+        }
+        let rows = kdbx::reader::parse(data, password)?;
+        Ok(Self::from_rows(ImportFormat::Kdbx, rows, collection)?)
+    }
+
+    /// Plans the import of a decrypted portable archive, including its empty categories
+    pub fn prepare_portable(vault: &PortableVault, collection: &EntryCollection) -> Result<Self, ImportError> {
+        let rows = vault.entries
+            .iter()
+            .enumerate()
+            .map(|(index, data)| ParsedRow {
+                row: index + 1,
+                title: Some(data.title.clone()),
+                outcome: RowOutcome::Data(Box::new(data.clone())),
+            })
+            .collect();
+        let mut plan = Self::from_rows(ImportFormat::PawPass, rows, collection)?;
+
+        for name in &vault.categories {
+            let exists = collection.categories.get_category_id_by_name(name).is_some()
+                || plan.report.new_categories.iter().any(|new| new.eq_ignore_ascii_case(name));
+            if !exists {
+                plan.report.new_categories.push(name.clone());
+                plan.empty_categories.push(name.clone());
+            }
+        }
+        Ok(plan)
+    }
+
+    fn from_rows(
+        format: ImportFormat,
+        rows: Vec<ParsedRow>,
+        collection: &EntryCollection,
+    ) -> Result<Self, ImportError> {
+        if rows.len() > MAX_IMPORT_ROWS {
+            return Err(ImportError::TooLarge(format!("exports are limited to {} items", MAX_IMPORT_ROWS)));
+        }
+
+        // Entries count as duplicates when title, username and URL all match
+        let key = |data: &EntryData| (
+            data.title.to_lowercase(),
+            data.username.clone().unwrap_or_default().to_lowercase(),
+            data.url.clone().unwrap_or_default().to_lowercase(),
+        );
+        let mut seen: HashSet<_> = collection.entries
+            .values()
+            .map(|entry| (
+                entry.title().to_lowercase(),
+                entry.username().unwrap_or_default().to_lowercase(),
+                entry.url().unwrap_or_default().to_lowercase(),
+            ))
+            .collect();
+
+        let mut report = ImportReport {
+            format,
+            dry_run: true,
+            total_rows: rows.len(),
+            imported: 0,
+            new_categories: Vec::new(),
+            skipped: Vec::new(),
+            invalid: Vec::new(),
+        };
+        let mut entries = Vec::new();
+
+        for ParsedRow { row, title, outcome } in rows {
+            let issue = |reason: String| ImportIssue { row, title: title.clone(), reason };
+
+            let data = match outcome {
+                RowOutcome::Entry(imported) if imported.is_empty() => {
+                    report.skipped.push(issue("Row is empty".to_string()));
+                    continue;
+                }
+                RowOutcome::Entry(imported) => imported.into_entry_data(),
+                RowOutcome::Data(data) => *data,
+                RowOutcome::Skip(reason) => {
+                    report.skipped.push(issue(reason));
+                    continue;
+                }
+                RowOutcome::Invalid(reason) => {
+                    report.invalid.push(issue(reason));
+                    continue;
+                }
+            };
+
+            if let Err(e) = data.validate() {
+                report.invalid.push(issue(e.to_string()));
+                continue;
+            }
+            if !seen.insert(key(&data)) {
+                report.skipped.push(issue(EntryError::DuplicateEntry.to_string()));
+                continue;
+            }
+
+            if let Some(category) = &data.category_name {
+                let exists = collection.categories.get_category_id_by_name(category).is_some()
+                    || report.new_categories.iter().any(|name| name.eq_ignore_ascii_case(category));
+                if !exists {
+                    report.new_categories.push(category.clone());
+                }
+            }
+            entries.push(data);
+        }
+
+        report.imported = entries.len();
+        Ok(Self { report, entries, empty_categories: Vec::new() })
+    }
+
+    pub fn report(&self) -> &ImportReport {
+        &self.report
+    }
+
+    /// Adds every planned entry, or none of them: if any entry fails, the ones already added
+    /// and their new categories are removed again. The caller saves the vault once afterwards.
+    pub fn commit(
+        self,
+        collection: &mut EntryCollection,
+        key_hierarchy: &KeyHierarchy,
+    ) -> Result<(ImportReport, ImportBatch), EntryError> {
+        let existing_categories: HashSet<Uuid> = collection.categories
+            .get_all_categories()
+            .iter()
+            .map(|category| category.id())
+            .collect();
+
+        let mut batch = ImportBatch { entry_ids: Vec::new(), category_ids: Vec::new() };
+        let mut result = Ok(());
+        for name in self.empty_categories {
+            if let Err(e) = collection.categories.create_category(name) {
+                result = Err(e.into());
+                break;
+            }
+        }
+        if result.is_ok() {
+            for data in self.entries {
+                match collection.create_entry(data, key_hierarchy) {
+                    Ok(id) => batch.entry_ids.push(id),
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+        }
+
+        batch.category_ids = collection.categories
+            .get_all_categories()
+            .iter()
+            .map(|category| category.id())
+            .filter(|id| !existing_categories.contains(id))
+            .collect();
+
+        if let Err(e) = result {
+            batch.revert(collection);
+            return Err(e);
+        }
+
+        let mut report = self.report;
+        report.dry_run = false;
+        Ok((report, batch))
+    }
+}
@@ -0,0 +1,162 @@
+pub mod reader;
+pub mod writer;
+
+use crate::entry::kind::{EntryDetails, EntryKind, WifiSecurity};
+
+// Argon2d settings for exported databases, matching KeePassXC's defaults closely enough that
+// opening the file there doesn't take noticeably longer than its own databases
+pub const EXPORT_KDF_ITERATIONS: u64 = 10;
+pub const EXPORT_KDF_MEMORY: u64 = 64 * 1024 * 1024; // 64 MiB
+pub const EXPORT_KDF_PARALLELISM: u32 = 2;
+
+// Standard KeePass field names
+pub(crate) const TITLE: &str = "Title";
+pub(crate) const USERNAME: &str = "UserName";
+pub(crate) const PASSWORD: &str = "Password";
+pub(crate) const URL: &str = "URL";
+pub(crate) const NOTES: &str = "Notes";
+// KeePassXC stores an otpauth URI here
+pub(crate) const OTP: &str = "otp";
+
+// KeePass has no favorites or entry kinds, so both are recorded as tags
+pub(crate) const FAVORITE_TAG: &str = "Favorite";
+pub(crate) const KIND_TAG_PREFIX: &str = "PawPass:";
+
+const CARD_FIELDS: [&str; 6] = ["Cardholder Name", "Card Number", "Expiry Month", "Expiry Year", "Security Code", "PIN"];
+const IDENTITY_FIELDS: [&str; 7] = ["Full Name", "Email", "Phone", "Address", "Date of Birth", "National ID", "Passport Number"];
+const WIFI_FIELDS: [&str; 4] = ["SSID", "Security", "Passphrase", "Hidden Network"];
+const SSH_KEY_FIELDS: [&str; 3] = ["Private Key", "Public Key", "Key Passphrase"];
+
+/// Names of the KeePass fields that hold the details of `kind`
+pub(crate) fn detail_field_names(kind: EntryKind) -> &'static [&'static str] {
+    match kind {
+        EntryKind::PaymentCard => &CARD_FIELDS,
+        EntryKind::Identity => &IDENTITY_FIELDS,
+        EntryKind::Wifi => &WIFI_FIELDS,
+        EntryKind::SshKey => &SSH_KEY_FIELDS,
+        EntryKind::Login | EntryKind::SecureNote => &[],
+    }
+}
+
+fn wifi_security_name(security: WifiSecurity) -> &'static str {
+    match security {
+        WifiSecurity::Open => "Open",
+        WifiSecurity::Wep => "WEP",
+        WifiSecurity::WpaPersonal => "WPA Personal",
+        WifiSecurity::WpaEnterprise => "WPA Enterprise",
+    }
+}
+
+/// Flattens the details into named KeePass fields; the flag marks values KeePass should protect
+pub(crate) fn details_to_fields(details: &EntryDetails) -> Vec<(&'static str, String, bool)> {
+    let mut fields = Vec::new();
+    let mut push = |name: &'static str, value: Option<&String>, protected: bool| {
+        if let Some(value) = value {
+            fields.push((name, value.clone(), protected));
+        }
+    };
+
+    match details {
+        EntryDetails::PaymentCard { cardholder_name, number, expiry_month, expiry_year, security_code, pin } => {
+            push(CARD_FIELDS[0], cardholder_name.as_ref(), false);
+            push(CARD_FIELDS[1], Some(number), true);
+            push(CARD_FIELDS[2], Some(&format!("{:02}", expiry_month)), false);
+            push(CARD_FIELDS[3], Some(&expiry_year.to_string()), false);
+            push(CARD_FIELDS[4], security_code.as_ref(), true);
+            push(CARD_FIELDS[5], pin.as_ref(), true);
+        }
+        EntryDetails::Identity { full_name, email, phone, address, date_of_birth, national_id, passport_number } => {
+            push(IDENTITY_FIELDS[0], full_name.as_ref(), false);
+            push(IDENTITY_FIELDS[1], email.as_ref(), false);
+            push(IDENTITY_FIELDS[2], phone.as_ref(), false);
+            push(IDENTITY_FIELDS[3], address.as_ref(), false);
+            push(IDENTITY_FIELDS[4], date_of_birth.as_ref(), false);
+            push(IDENTITY_FIELDS[5], national_id.as_ref(), true);
+            push(IDENTITY_FIELDS[6], passport_number.as_ref(), true);
+        }
+        EntryDetails::Wifi { ssid, security, passphrase, hidden } => {
+            push(WIFI_FIELDS[0], Some(ssid), false);
+            push(WIFI_FIELDS[1], Some(&wifi_security_name(*security).to_string()), false);
+            push(WIFI_FIELDS[2], passphrase.as_ref(), true);
+            push(WIFI_FIELDS[3], Some(&hidden.to_string()), false);
+        }
+        EntryDetails::SshKey { private_key, public_key, passphrase } => {
+            push(SSH_KEY_FIELDS[0], Some(private_key), true);
+            push(SSH_KEY_FIELDS[1], public_key.as_ref(), false);
+            push(SSH_KEY_FIELDS[2], passphrase.as_ref(), true);
+        }
+    }
+
+    fields
+}
+
+/// Rebuilds the details of `kind` from fields written by `details_to_fields`.
+/// Errors are reasons for the import report.
+pub(crate) fn details_from_fields(
+    kind: EntryKind,
+    field: impl Fn(&str) -> Option<String>,
+) -> Result<Option<EntryDetails>, String> {
+    let optional = |name: &str| field(name).filter(|value| !value.is_empty());
+    let required = |name: &str| optional(name).ok_or_else(|| format!("Missing the {} field", name));
+    let number = |name: &str| required(name)?
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("The {} field is not a number", name));
+
+    let details = match kind {
+        EntryKind::Login | EntryKind::SecureNote => return Ok(None),
+        EntryKind::PaymentCard => EntryDetails::PaymentCard {
+            cardholder_name: optional(CARD_FIELDS[0]),
+            number: required(CARD_FIELDS[1])?,
+            expiry_month: number(CARD_FIELDS[2])?,
+            expiry_year: number(CARD_FIELDS[3])?,
+            security_code: optional(CARD_FIELDS[4]),
+            pin: optional(CARD_FIELDS[5]),
+        },
+        EntryKind::Identity => EntryDetails::Identity {
+            full_name: optional(IDENTITY_FIELDS[0]),
+            email: optional(IDENTITY_FIELDS[1]),
+            phone: optional(IDENTITY_FIELDS[2]),
+            address: optional(IDENTITY_FIELDS[3]),
+            date_of_birth: optional(IDENTITY_FIELDS[4]),
+            national_id: optional(IDENTITY_FIELDS[5]),
+            passport_number: optional(IDENTITY_FIELDS[6]),
+        },
+        EntryKind::Wifi => {
+            let security = required(WIFI_FIELDS[1])?;
+            EntryDetails::Wifi {
+                ssid: required(WIFI_FIELDS[0])?,
+                security: [WifiSecurity::Open, WifiSecurity::Wep, WifiSecurity::WpaPersonal, WifiSecurity::WpaEnterprise]
+                    .into_iter()
+                    .find(|candidate| wifi_security_name(*candidate).eq_ignore_ascii_case(security.trim()))
+                    .ok_or_else(|| format!("Unknown Wi-Fi security '{}'", security))?,
+                passphrase: optional(WIFI_FIELDS[2]),
+                hidden: optional(WIFI_FIELDS[3]).is_some_and(|hidden| hidden.trim().eq_ignore_ascii_case("true")),
+            }
+        }
+        EntryKind::SshKey => EntryDetails::SshKey {
+            private_key: required(SSH_KEY_FIELDS[0])?,
+            public_key: optional(SSH_KEY_FIELDS[1]),
+            passphrase: optional(SSH_KEY_FIELDS[2]),
+        },
+    };
+
+    Ok(Some(details))
+}
+
+/// Tag recording the entry kind, e.g. `PawPass:paymentCard`
+pub(crate) fn kind_tag(kind: EntryKind) -> Option<String> {
+    match kind {
+        EntryKind::Login => None,
+        kind => serde_json::to_value(kind)
+            .ok()
+            .and_then(|value| value.as_str().map(|name| format!("{}{}", KIND_TAG_PREFIX, name))),
+    }
+}
+
+pub(crate) fn kind_from_tags(tags: &[String]) -> EntryKind {
+    tags.iter()
+        .find_map(|tag| tag.strip_prefix(KIND_TAG_PREFIX))
+        .and_then(|name| serde_json::from_value(serde_json::Value::String(name.to_string())).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+use keepass::{
+    db::{Entry, Group, Node, Value},
+    error::{DatabaseKeyError, DatabaseOpenError},
+    Database, DatabaseKey,
+};
+use uuid::Uuid;
+use crate::entry::custom_field::{CustomFieldData, CustomFieldType};
+use crate::error::kdbx_error::KdbxError;
+use crate::import::{ImportedRow, ParsedRow, RowOutcome};
+use super::{
+    detail_field_names, details_from_fields, kind_from_tags,
+    FAVORITE_TAG, NOTES, OTP, PASSWORD, TITLE, URL, USERNAME,
+};
+
+// KeePass 2.47+ stores TOTP settings in these fields instead of an otpauth URI
+const TIME_OTP_SECRET: &str = "TimeOtp-Secret-Base32";
+const TIME_OTP_LENGTH: &str = "TimeOtp-Length";
+const TIME_OTP_PERIOD: &str = "TimeOtp-Period";
+const TIME_OTP_ALGORITHM: &str = "TimeOtp-Algorithm";
+
+fn map_open_error(error: DatabaseOpenError) -> KdbxError {
+    match error {
+        DatabaseOpenError::Key(DatabaseKeyError::IncorrectKey) => KdbxError::IncorrectPassword,
+        DatabaseOpenError::UnsupportedVersion => KdbxError::UnsupportedVersion,
+        other => KdbxError::Corrupted(other.to_string()),
+    }
+}
+
+/// Decrypts a KDBX database and turns every entry into an import row. Groups become categories
+/// named after the innermost group; entries in the recycle bin are skipped.
+pub(crate) fn parse(data: &[u8], password: &str) -> Result<Vec<ParsedRow>, KdbxError> {
+    let database = Database::parse(data, DatabaseKey::new().with_password(password))
+        .map_err(map_open_error)?;
+
+    let mut rows = Vec::new();
+    collect(&database.root, None, database.meta.recyclebin_uuid, false, &mut rows);
+    Ok(rows)
+}
+
+fn collect(
+    group: &Group,
+    category: Option<&str>,
+    recycle_bin: Option<Uuid>,
+    in_recycle_bin: bool,
+    rows: &mut Vec<ParsedRow>,
+) {
+    for node in &group.children {
+        match node {
+            Node::Group(child) => {
+                let in_recycle_bin = in_recycle_bin || Some(child.uuid) == recycle_bin;
+                collect(child, Some(&child.name), recycle_bin, in_recycle_bin, rows);
+            }
+            Node::Entry(entry) => {
+                let outcome = if in_recycle_bin {
+                    RowOutcome::Skip("Item is in the recycle bin".to_string())
+                } else {
+                    convert(entry, category)
+                };
+                rows.push(ParsedRow {
+                    row: rows.len() + 1,
+                    title: entry.get_title().filter(|title| !title.is_empty()).map(str::to_string),
+                    outcome,
+                });
+            }
+        }
+    }
+}
+
+// Builds an otpauth URI from KeePass's own TOTP fields
+fn time_otp_uri(entry: &Entry) -> Option<String> {
+    let secret = entry.get(TIME_OTP_SECRET).filter(|secret| !secret.is_empty())?;
+    let mut uri = format!("otpauth://totp/KeePass?secret={}", secret.replace(' ', ""));
+    if let Some(digits) = entry.get(TIME_OTP_LENGTH) {
+        uri.push_str(&format!("&digits={}", digits.trim()));
+    }
+    if let Some(period) = entry.get(TIME_OTP_PERIOD) {
+        uri.push_str(&format!("&period={}", period.trim()));
+    }
+    if let Some(algorithm) = entry.get(TIME_OTP_ALGORITHM) {
+        // Stored as e.g. HMAC-SHA-256
+        let algorithm = algorithm.trim().trim_start_matches("HMAC-").replace('-', "");
+        uri.push_str(&format!("&algorithm={}", algorithm));
+    }
+    Some(uri)
+}
+
+fn convert(entry: &Entry, category: Option<&str>) -> RowOutcome {
+    let field = |name: &str| entry.get(name).unwrap_or_default().to_string();
+
+    let kind = kind_from_tags(&entry.tags);
+    let details = match details_from_fields(kind, |name| entry.get(name).map(str::to_string)) {
+        Ok(details) => details,
+        Err(reason) => return RowOutcome::Invalid(reason),
+    };
+
+    let totp = entry.get(OTP)
+        .filter(|otp| !otp.is_empty())
+        .map(str::to_string)
+        .or_else(|| time_otp_uri(entry))
+        .unwrap_or_default();
+
+    // Everything that isn't a standard, TOTP or detail field is carried over as a custom field
+    let consumed: HashSet<&str> = [TITLE, USERNAME, PASSWORD, URL, NOTES, OTP]
+        .into_iter()
+        .chain([TIME_OTP_SECRET, TIME_OTP_LENGTH, TIME_OTP_PERIOD, TIME_OTP_ALGORITHM])
+        .chain(detail_field_names(kind).iter().copied())
+        .collect();
+    let mut custom_fields: Vec<CustomFieldData> = entry.fields
+        .iter()
+        .filter(|(name, _)| !consumed.contains(name.as_str()))
+        .filter_map(|(name, value)| {
+            let field_type = match value {
+                Value::Protected(_) => CustomFieldType::Hidden,
+                Value::Unprotected(_) => CustomFieldType::Text,
+                Value::Bytes(_) => return None,
+            };
+            Some(CustomFieldData {
+                name: name.clone(),
+                field_type,
+                value: entry.get(name).unwrap_or_default().to_string(),
+                searchable: false,
+            })
+        })
+        .collect();
+    // Field order isn't preserved by the database, so keep imports stable at least
+    custom_fields.sort_by(|a, b| a.name.cmp(&b.name));
+
+    RowOutcome::Entry(Box::new(ImportedRow {
+        title: field(TITLE),
+        username: field(USERNAME),
+        password: field(PASSWORD),
+        url: field(URL),
+        notes: field(NOTES),
+        totp,
+        category: category.unwrap_or_default().to_string(),
+        favorite: entry.tags.iter().any(|tag| tag.eq_ignore_ascii_case(FAVORITE_TAG)),
+        kind,
+        details,
+        custom_fields,
+    }))
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use keepass::{
+    config::{DatabaseConfig, KdfConfig},
+    db::{Entry, Group, Value},
+    Database, DatabaseKey,
+};
+use crate::crypto::KeyHierarchy;
+use crate::entry::collection::EntryCollection;
+use crate::entry::custom_field::CustomFieldType;
+use crate::entry::entry::DecryptedEntry;
+use crate::error::app_error::AppError;
+use crate::error::kdbx_error::KdbxError;
+use super::{
+    details_to_fields, kind_tag,
+    EXPORT_KDF_ITERATIONS, EXPORT_KDF_MEMORY, EXPORT_KDF_PARALLELISM,
+    FAVORITE_TAG, NOTES, OTP, PASSWORD, TITLE, URL, USERNAME,
+};
+
+fn unprotected(entry: &mut Entry, name: &str, value: Option<String>) {
+    if let Some(value) = value {
+        entry.fields.insert(name.to_string(), Value::Unprotected(value));
+    }
+}
+
+fn protected(entry: &mut Entry, name: &str, value: Option<String>) {
+    if let Some(value) = value {
+        entry.fields.insert(name.to_string(), Value::Protected(value.into()));
+    }
+}
+
+fn convert(decrypted: &DecryptedEntry) -> Entry {
+    let mut entry = Entry::new();
+
+    unprotected(&mut entry, TITLE, Some(decrypted.title.clone()));
+    unprotected(&mut entry, USERNAME, decrypted.username.clone());
+    protected(&mut entry, PASSWORD, decrypted.password.clone());
+    unprotected(&mut entry, URL, decrypted.url.clone());
+    unprotected(&mut entry, NOTES, decrypted.notes.clone());
+    protected(&mut entry, OTP, decrypted.totp.clone());
+
+    if let Some(details) = &decrypted.details {
+        for (name, value, is_protected) in details_to_fields(details) {
+            if is_protected {
+                protected(&mut entry, name, Some(value));
+            } else {
+                unprotected(&mut entry, name, Some(value));
+            }
+        }
+    }
+
+    for field in &decrypted.custom_fields {
+        if field.field_type == CustomFieldType::Hidden {
+            protected(&mut entry, &field.name, Some(field.value.clone()));
+        } else {
+            unprotected(&mut entry, &field.name, Some(field.value.clone()));
+        }
+    }
+
+    if decrypted.favorite {
+        entry.tags.push(FAVORITE_TAG.to_string());
+    }
+    entry.tags.extend(kind_tag(decrypted.kind));
+
+    entry.times.set_creation(decrypted.created_at.naive_utc());
+    entry.times.set_last_modification(decrypted.updated_at.naive_utc());
+    entry
+}
+
+/// Writes every entry to a KDBX 4 database protected by `password`. Categories become groups
+/// under the root; attachments and password history are not included.
+pub fn export(
+    collection: &EntryCollection,
+    key_hierarchy: &KeyHierarchy,
+    password: &str,
+) -> Result<Vec<u8>, AppError> {
+    let mut config = DatabaseConfig::default();
+    if let KdfConfig::Argon2 { iterations, memory, parallelism, .. } = &mut config.kdf_config {
+        *iterations = EXPORT_KDF_ITERATIONS;
+        *memory = EXPORT_KDF_MEMORY;
+        *parallelism = EXPORT_KDF_PARALLELISM;
+    }
+
+    let mut database = Database::new(config);
+    database.root.name = "PawPass".to_string();
+    database.meta.generator = Some("PawPass".to_string());
+    database.meta.database_name = Some("PawPass".to_string());
+
+    let mut groups: BTreeMap<String, Group> = BTreeMap::new();
+    let mut uncategorized = Vec::new();
+    let mut decrypted_entries = collection.entries
+        .keys()
+        .map(|id| collection.get_decrypted_entry(id, key_hierarchy))
+        .collect::<Result<Vec<_>, _>>()?;
+    decrypted_entries.sort_by_key(|entry| entry.title.to_lowercase());
+
+    for decrypted in decrypted_entries {
+        match &decrypted.category_name {
+            Some(name) => groups
+                .entry(name.clone())
+                .or_insert_with(|| Group::new(name))
+                .add_child(convert(&decrypted)),
+            None => uncategorized.push(convert(&decrypted)),
+        }
+    }
+    for group in groups.into_values() {
+        database.root.add_child(group);
+    }
+    for entry in uncategorized {
+        database.root.add_child(entry);
+    }
+
+    let mut output = Vec::new();
+    database
+        .save(&mut output, DatabaseKey::new().with_password(password))
+        .map_err(|e| KdbxError::WriteFailed(e.to_string()))?;
+    Ok(output)
+}
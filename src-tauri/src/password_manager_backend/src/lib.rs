@@ -3,4 +3,7 @@ pub mod error;
 pub mod entry;
 pub mod category_favorite;
 pub mod auth;
-pub mod _vault;
\ No newline at end of file
+pub mod _vault;
+pub mod import;
+pub mod kdbx;
+pub mod audit;
\ No newline at end of file
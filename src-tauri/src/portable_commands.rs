@@ -0,0 +1,97 @@
+use crate::{helpers, protected_command, VaultState};
+use std::path::PathBuf;
+use password_manager_backend::{
+    audit::strength::PasswordPolicy,
+    _vault::portable::{PortableImportMode, PortableVault},
+    entry::collection::EntryCollection,
+    error::app_error::AppError,
+    import::{ImportPlan, ImportReport},
+};
+use tauri::State;
+
+// Writes the decrypted vault to a password-protected archive that any PawPass install can import
+#[tauri::command]
+pub async fn export_portable_vault(
+    state: State<'_, VaultState>,
+    destination_path: String,
+    password: String,
+    confirm_password: String,
+) -> Result<(), AppError> {
+    protected_command!(state, {
+        helpers::validate_password(&password, &confirm_password, &PasswordPolicy::default(), None)?;
+
+        let vault_manager_lock = state.vault_manager.read().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_ref().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        let data = PortableVault::from_vault(manager, key_hierarchy)?.seal(&password)?;
+        tokio::fs::write(PathBuf::from(destination_path), data).await?;
+
+        println!("Exported {} entries to a portable archive", manager.entry_collection.entries.len());
+        Ok(())
+    })
+}
+
+// Imports a portable archive. Merging adds the archive's entries next to the current ones and skips
+// duplicates; replacing swaps out every entry and category and adopts the archive's generator settings.
+#[tauri::command]
+pub async fn import_portable_vault(
+    state: State<'_, VaultState>,
+    file_path: String,
+    password: String,
+    mode: PortableImportMode,
+    dry_run: bool,
+) -> Result<ImportReport, AppError> {
+    protected_command!(state, {
+        let file_path = PathBuf::from(file_path);
+        if !file_path.exists() {
+            return Err(AppError::PathNotFound(file_path.to_string_lossy().to_string()));
+        }
+        let data = tokio::fs::read(&file_path).await?;
+        let archive = PortableVault::open(&data, &password)?;
+
+        let mut vault_manager_lock = state.vault_manager.write().await;
+        let key_hierarchy_lock = state.key_hierarchy.read().await;
+        let manager = vault_manager_lock.as_mut().ok_or(AppError::VaultNotFound)?;
+        let key_hierarchy = key_hierarchy_lock.as_ref().ok_or(AppError::VaultLocked)?;
+
+        match mode {
+            PortableImportMode::Merge => {
+                let plan = ImportPlan::prepare_portable(&archive, &manager.entry_collection)?;
+                if dry_run {
+                    return Ok(plan.report().clone());
+                }
+
+                let (report, batch) = plan.commit(&mut manager.entry_collection, key_hierarchy)?;
+                if let Err(e) = state.storage.save_vault(manager, key_hierarchy).await {
+                    batch.revert(&mut manager.entry_collection);
+                    return Err(e);
+                }
+                Ok(report)
+            }
+            PortableImportMode::Replace => {
+                let mut replacement = EntryCollection::new();
+                let plan = ImportPlan::prepare_portable(&archive, &replacement)?;
+                if dry_run {
+                    return Ok(plan.report().clone());
+                }
+                let (report, _) = plan.commit(&mut replacement, key_hierarchy)?;
+
+                // Everything is swapped in memory first and swapped back if the save fails
+                let previous_settings = manager.password_generator_settings.get_settings();
+                manager.password_generator_settings.configure(archive.password_generator_settings.clone())?;
+                let previous = std::mem::replace(&mut manager.entry_collection, replacement);
+                if let Err(e) = state.storage.save_vault(manager, key_hierarchy).await {
+                    manager.entry_collection = previous;
+                    manager.password_generator_settings.configure(previous_settings)?;
+                    return Err(e);
+                }
+
+                // The replaced entries' attachment files are no longer referenced
+                state.storage.remove_orphaned_attachments(&manager.entry_collection.attachment_ids()).await?;
+                Ok(report)
+            }
+        }
+    })
+}
@@ -0,0 +1,142 @@
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+use password_manager_backend::{_vault::auto_lock::AutoLockSettings, error::app_error::AppError};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use zeroize::Zeroize;
+
+use crate::VaultState;
+
+pub const VAULT_LOCKED_EVENT: &str = "vault-locked";
+
+// How often the monitor wakes up to check the session
+const MONITOR_INTERVAL: Duration = Duration::from_secs(5);
+// The monotonic clock stops while the machine sleeps but the wall clock does not,
+// so a gap this large between the two during one check means we were suspended
+const SUSPEND_GAP: Duration = Duration::from_secs(30);
+
+/// Sent as the payload of the `vault-locked` event
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LockReason {
+    Idle,
+    Suspend,
+}
+
+struct Activity {
+    instant: Instant,
+    wall: SystemTime,
+}
+
+/// Tracks activity for the unlocked session so it can be locked automatically
+pub struct SessionManager {
+    last_activity: Mutex<Activity>,
+    settings: Mutex<AutoLockSettings>,
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self {
+            last_activity: Mutex::new(Activity { instant: Instant::now(), wall: SystemTime::now() }),
+            settings: Mutex::new(AutoLockSettings::default()),
+        }
+    }
+}
+
+impl SessionManager {
+    /// Resets the idle timer; called by every `protected_command!`
+    pub fn touch(&self) {
+        let mut activity = self.last_activity.lock().unwrap_or_else(PoisonError::into_inner);
+        *activity = Activity { instant: Instant::now(), wall: SystemTime::now() };
+    }
+
+    /// Applies the vault's settings and starts the idle timer for a fresh session
+    pub fn start(&self, settings: AutoLockSettings) {
+        self.configure(settings);
+        self.touch();
+    }
+
+    pub fn configure(&self, settings: AutoLockSettings) {
+        *self.settings.lock().unwrap_or_else(PoisonError::into_inner) = settings;
+    }
+
+    pub fn settings(&self) -> AutoLockSettings {
+        *self.settings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Uses whichever clock saw more time pass, so sleeping does not pause the idle timer
+    fn idle_expired(&self) -> bool {
+        let Some(timeout) = self.settings().idle_timeout() else {
+            return false;
+        };
+
+        let activity = self.last_activity.lock().unwrap_or_else(PoisonError::into_inner);
+        let monotonic = activity.instant.elapsed();
+        let wall = SystemTime::now().duration_since(activity.wall).unwrap_or_default();
+
+        monotonic.max(wall) >= timeout
+    }
+}
+
+/// Saves the vault, then zeroizes and drops the key hierarchy and vault manager.
+/// The session is locked even if the save fails.
+pub async fn lock_session(state: &VaultState) -> Result<(), AppError> {
+    let mut vault_manager_lock = state.vault_manager.write().await;
+    let mut key_hierarchy_lock = state.key_hierarchy.write().await;
+
+    let save_result = match (vault_manager_lock.as_mut(), key_hierarchy_lock.as_ref()) {
+        (Some(manager), Some(key_hierarchy)) => state.storage.save_vault(manager, key_hierarchy).await,
+        _ => Ok(()),
+    };
+
+    state.set_session_active(false).await;
+
+    if let Some(mut key_hierarchy) = key_hierarchy_lock.take() {
+        key_hierarchy.zeroize();
+    }
+    if let Some(mut manager) = vault_manager_lock.take() {
+        manager.zeroize();
+    }
+
+    save_result
+}
+
+/// Background task that locks the vault after the idle timeout or a detected suspend
+pub fn spawn_auto_lock_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_instant = Instant::now();
+        let mut last_wall = SystemTime::now();
+
+        loop {
+            tokio::time::sleep(MONITOR_INTERVAL).await;
+
+            let (now_instant, now_wall) = (Instant::now(), SystemTime::now());
+            let monotonic = now_instant.duration_since(last_instant);
+            let wall = now_wall.duration_since(last_wall).unwrap_or_default();
+            let suspended = wall > monotonic + SUSPEND_GAP;
+            (last_instant, last_wall) = (now_instant, now_wall);
+
+            let state = app.state::<VaultState>();
+            if !state.is_session_active().await {
+                continue;
+            }
+
+            let reason = if suspended && state.session.settings().lock_on_suspend {
+                LockReason::Suspend
+            } else if state.session.idle_expired() {
+                LockReason::Idle
+            } else {
+                continue;
+            };
+
+            println!("Auto-locking vault: {:?}", reason);
+            if let Err(e) = lock_session(&state).await {
+                println!("Failed to save vault while auto-locking: {:?}", e);
+            }
+
+            if let Err(e) = app.emit(VAULT_LOCKED_EVENT, reason) {
+                println!("Failed to emit {} event: {:?}", VAULT_LOCKED_EVENT, e);
+            }
+        }
+    });
+}